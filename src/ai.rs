@@ -0,0 +1,265 @@
+use std::time::Duration;
+
+use crate::{geometry::Rectangle, random::Random};
+
+/// The preset which defines how well a computer player plays.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    Impossible,
+}
+
+impl Difficulty {
+    /// All difficulties from the easiest to the hardest.
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Impossible,
+    ];
+
+    /// Get the name of the difficulty shown to the players.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "EASY",
+            Difficulty::Normal => "NORMAL",
+            Difficulty::Hard => "HARD",
+            Difficulty::Impossible => "IMPOSSIBLE",
+        }
+    }
+
+    /// Find the difficulty with the given name ignoring the case.
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.label().eq_ignore_ascii_case(label))
+    }
+
+    /// Get the next harder difficulty or the easiest one after the hardest.
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|d| d == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Get the previous easier difficulty or the hardest one before the easiest.
+    pub fn previous(&self) -> Self {
+        let index = Self::ALL.iter().position(|d| d == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Get the delay before the computer player reacts to a ball moving towards it.
+    fn reaction(&self) -> Duration {
+        match self {
+            Difficulty::Easy => Duration::from_millis(300),
+            Difficulty::Normal => Duration::from_millis(180),
+            Difficulty::Hard => Duration::from_millis(90),
+            Difficulty::Impossible => Duration::ZERO,
+        }
+    }
+
+    /// Get the maximum paddle movement relative to the full paddle velocity.
+    fn speed(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 0.7,
+            Difficulty::Hard => 0.9,
+            Difficulty::Impossible => 1.0,
+        }
+    }
+
+    /// Get the maximum error in the predicted position of the ball.
+    fn error(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.12,
+            Difficulty::Normal => 0.06,
+            Difficulty::Hard => 0.02,
+            Difficulty::Impossible => 0.0,
+        }
+    }
+
+    /// Get the probability of deliberately missing the ball.
+    fn mistakes(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.2,
+            Difficulty::Normal => 0.1,
+            Difficulty::Hard => 0.04,
+            Difficulty::Impossible => 0.0,
+        }
+    }
+}
+
+/// The information about the court which a computer player uses to move its paddle.
+pub struct View<'a> {
+    pub ball: &'a Rectangle,
+    pub ball_x_movement: f32,
+    pub ball_y_movement: f32,
+    pub paddle: &'a Rectangle,
+    pub top: f32,
+    pub bottom: f32,
+    pub reach: f32,
+}
+
+/// A computer player which moves its paddle towards the predicted position of the ball.
+#[derive(Clone, Debug)]
+pub struct Ai {
+    random: Random,
+    difficulty: Difficulty,
+    aim: f32,
+    error: f32,
+    delay: Duration,
+    approaching: bool,
+}
+
+impl Ai {
+    /// Build a new computer player which behaves identically for the same seed and difficulty.
+    pub fn new(seed: u64, difficulty: Difficulty) -> Self {
+        Self {
+            random: Random::new(seed),
+            difficulty,
+            aim: 0.0,
+            error: 0.0,
+            delay: Duration::ZERO,
+            approaching: false,
+        }
+    }
+
+    /// Get the paddle movement in range [-1, 1] for the given view of the court and time step.
+    ///
+    /// The reach of the view is the distance the paddle would travel with the full movement.
+    pub fn movement(&mut self, view: &View, dt: Duration) -> f32 {
+        let approaching = approaches(view);
+        if approaching && !self.approaching {
+            // Pick a new spot on the paddle to hit the ball with to make the returns less uniform.
+            self.aim = self.random.range(-0.8, 0.8);
+            if self.random.next_f32() < self.difficulty.mistakes() {
+                self.aim = self.aim.signum() * self.random.range(1.4, 2.0);
+            }
+            self.error = self.random.range(-1.0, 1.0) * self.difficulty.error();
+            self.delay = self.difficulty.reaction();
+        }
+        self.approaching = approaching;
+
+        // Stay still until the computer player has reacted to the changed ball direction.
+        self.delay -= Duration::min(self.delay, dt);
+        if !self.delay.is_zero() {
+            return 0.0;
+        }
+
+        let target = if approaching {
+            predict(view) + self.error + view.ball.h / 2.0 + self.aim * view.paddle.h / 2.0
+        } else {
+            (view.top + view.bottom) / 2.0
+        };
+        let distance = target - (view.paddle.y + view.paddle.h / 2.0);
+        if view.reach <= 0.0 {
+            return 0.0;
+        }
+        let speed = self.difficulty.speed();
+        (distance / view.reach).clamp(-speed, speed)
+    }
+}
+
+/// A deterministic computer player which keeps its paddle centered on the ball with a limited speed.
+///
+/// The bot neither predicts nor randomizes its movement, which makes it a fixed opponent to measure
+/// the difficulties against. It misses the steep returns which outrun its paddle.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReferenceBot;
+
+impl ReferenceBot {
+    /// The maximum paddle movement relative to the full paddle velocity.
+    const SPEED: f32 = 0.4;
+
+    /// Get the paddle movement in range [-1, 1] for the given view of the court.
+    pub fn movement(&self, view: &View) -> f32 {
+        if view.reach <= 0.0 {
+            return 0.0;
+        }
+        let target = view.ball.y + view.ball.h / 2.0;
+        let distance = target - (view.paddle.y + view.paddle.h / 2.0);
+        (distance / view.reach).clamp(-Self::SPEED, Self::SPEED)
+    }
+}
+
+/// Check whether the ball is moving towards the paddle.
+fn approaches(view: &View) -> bool {
+    if view.paddle.x > view.ball.x {
+        view.ball_x_movement > 0.0
+    } else {
+        view.ball_x_movement < 0.0
+    }
+}
+
+/// Predict the Y-coordinate of the ball when it reaches the paddle by folding the wall bounces.
+fn predict(view: &View) -> f32 {
+    let contact = if view.paddle.x > view.ball.x {
+        view.paddle.x - view.ball.w
+    } else {
+        view.paddle.x + view.paddle.w
+    };
+    let time = (contact - view.ball.x) / view.ball_x_movement;
+    let y = view.ball.y + view.ball_y_movement * time;
+    let span = view.bottom - view.top - view.ball.h;
+    if span <= 0.0 {
+        return view.top;
+    }
+    let mut offset = (y - view.top).rem_euclid(span * 2.0);
+    if offset > span {
+        offset = span * 2.0 - offset;
+    }
+    view.top + offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Get the movements of a computer player with the given seed during a few rallies where the
+    /// ball approaches its paddle from different heights and then moves away again.
+    fn movements(seed: u64) -> Vec<f32> {
+        let mut ai = Ai::new(seed, Difficulty::Easy);
+        let paddle = Rectangle {
+            x: 0.9,
+            y: 0.4,
+            w: 0.02,
+            h: 0.2,
+        };
+        let dt = Duration::from_millis(10);
+        let mut movements = Vec::new();
+        for rally in 0..20 {
+            let start_y = 0.1 + 0.04 * rally as f32;
+            for step in 0..120 {
+                let (x, x_movement) = if step < 80 {
+                    (0.1 + 0.01 * step as f32, 1.0)
+                } else {
+                    (0.9 - 0.01 * (step - 80) as f32, -1.0)
+                };
+                let ball = Rectangle {
+                    x,
+                    y: start_y,
+                    w: 0.025,
+                    h: 0.0325,
+                };
+                let view = View {
+                    ball: &ball,
+                    ball_x_movement: x_movement,
+                    ball_y_movement: 0.5,
+                    paddle: &paddle,
+                    top: 0.03,
+                    bottom: 0.97,
+                    reach: 0.01,
+                };
+                movements.push(ai.movement(&view, dt));
+            }
+        }
+        movements
+    }
+
+    #[test]
+    fn same_seed_moves_the_same_and_other_seeds_differ() {
+        assert_eq!(movements(7), movements(7));
+        assert_ne!(movements(7), movements(8));
+    }
+}
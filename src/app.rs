@@ -1,61 +1,464 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use crate::{
-    graphics::Graphics,
-    scenes::{MainMenu, Scene},
-    timer::Timer,
+    audio::{AudioSink, NullSink},
+    bindings::RESERVED_KEY,
+    context::Context,
+    controller::InputSource,
+    input::{Action, InputEvent, Key, Player},
+    options::{Options, OPTIONS_FILE},
+    renderer::{window_to_view, Renderer},
+    scenes::{MainMenu, Scene, Transition},
+    screenshot,
+    timer::{Timer, STEP},
+    tuning::Tuning,
 };
 
 /// An application context including the necessary logical and graphical components.
 pub struct App {
-    graphics: Graphics,
-    scene: Option<Box<dyn Scene>>,
+    renderer: Box<dyn Renderer>,
+    scenes: Vec<Box<dyn Scene>>,
+    context: Context,
+    sources: Vec<Box<dyn InputSource>>,
+    /// The latest axis positions of the players which were routed to the topmost scene.
+    axes: [Option<f32>; 2],
+    audio: Box<dyn AudioSink>,
     timer: Timer,
+    /// The file where the options are loaded from and saved into.
+    options_file: PathBuf,
 }
 
 impl App {
-    /// Build a new application context and with the given graphical context.
-    pub fn new(gfx: Graphics) -> Self {
+    /// Build a new application context with the given rendering context and tunables.
+    pub fn new(renderer: Box<dyn Renderer>, tuning: Tuning) -> Self {
+        Self::with_scene(renderer, tuning, Box::new(MainMenu::new()))
+    }
+
+    /// Build a new application context with the given tunables which starts from the given scene.
+    ///
+    /// The saved options are loaded once into the context which is shared with the scenes. The
+    /// renderer uses the colors of the selected theme and the aspect ratio from the tunables, while
+    /// the keys are translated into actions with the saved bindings.
+    pub fn with_scene(
+        mut renderer: Box<dyn Renderer>,
+        tuning: Tuning,
+        mut scene: Box<dyn Scene>,
+    ) -> Self {
+        let options_file = PathBuf::from(OPTIONS_FILE);
+        let context = Context {
+            options: Options::load_or_default(&options_file),
+            tuning,
+        };
+        renderer.set_palette(context.options.theme.palette());
+        renderer.set_aspect(tuning.aspect);
+        scene.enter(&context);
         App {
-            graphics: gfx,
-            scene: Some(Box::new(MainMenu::new())),
+            renderer,
+            scenes: vec![scene],
+            context,
+            sources: Vec::new(),
+            axes: [None; 2],
+            audio: Box::new(NullSink::default()),
             timer: Timer::new(),
+            options_file,
+        }
+    }
+
+    /// Load the options from the given file and save them into it instead of the options file.
+    pub fn with_options_file(mut self, path: &Path) -> Self {
+        self.options_file = path.to_path_buf();
+        self.context.options = Options::load_or_default(path);
+        self.renderer
+            .set_palette(self.context.options.theme.palette());
+        if let Some(s) = self.scenes.last_mut() {
+            s.enter(&self.context);
         }
+        self
+    }
+
+    /// Poll the analog axes from the given input source in addition to the earlier sources.
+    pub fn with_source(mut self, source: Box<dyn InputSource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Play the sounds of the scenes with the given audio sink instead of keeping them silent.
+    pub fn with_audio(mut self, audio: Box<dyn AudioSink>) -> Self {
+        self.audio = audio;
+        self
     }
 
     /// Resize the application graphics rendering canvas.
     pub fn resize(&mut self) {
-        self.graphics.resize();
+        self.renderer.resize();
     }
 
-    /// Update the current state of the application logic.
+    /// Update the current state of the application logic with fixed simulation steps.
     pub fn tick(&mut self) {
-        if let Some(s) = self.scene.take() {
-            self.scene = s.tick(self.timer.time());
+        self.timer.accumulate();
+        while self.timer.consume() {
+            self.poll_sources();
+            self.audio.advance(STEP);
+            match self.scenes.last_mut() {
+                Some(s) => {
+                    let transition = s.tick(STEP);
+                    self.apply(transition);
+                }
+                None => break,
+            }
         }
     }
 
     /// Render the current state of the application on the screen.
     pub fn draw(&mut self) {
-        if let Some(s) = self.scene.as_ref() {
-            self.graphics.draw(s.as_ref());
+        if self.running() {
+            self.renderer.clear();
+            draw_scenes(&self.scenes, self.renderer.as_mut(), self.timer.alpha());
+            self.renderer.present();
+        }
+    }
+
+    /// Route the given input event to the topmost scene.
+    pub fn input(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::KeyDown(key) => self.key_down(key),
+            InputEvent::KeyUp(key) => self.key_up(key),
+            InputEvent::MouseMove { x, y } => self.mouse(x, y, |s, x, y| s.mouse_move(x, y)),
+            InputEvent::MouseClick { x, y } => self.mouse(x, y, |s, x, y| s.mouse_click(x, y)),
         }
     }
 
     /// Tell the application that a keyboard key is being pressed.
-    pub fn key_down(&mut self, key: u16) {
-        if let Some(s) = self.scene.take() {
-            self.scene = s.key_down(key);
+    pub fn key_down(&mut self, key: Key) {
+        if key == RESERVED_KEY {
+            return;
         }
+        self.route_actions(key, |s, action| s.action_down(action));
     }
 
     /// Tell the application that a keyboard key is being released.
-    pub fn key_up(&mut self, key: u16) {
-        if let Some(s) = self.scene.take() {
-            self.scene = s.key_up(key);
+    ///
+    /// The screenshot is taken on the release, as a held key repeats its presses.
+    pub fn key_up(&mut self, key: Key) {
+        if key == RESERVED_KEY {
+            let path = screenshot_path();
+            if let Err(error) = self.screenshot(&path) {
+                eprintln!("Failed to save screenshot {}: {}", path.display(), error);
+            }
+            return;
+        }
+        if let Some(s) = self.scenes.last_mut() {
+            if let Some(transition) = s.capture_key(key) {
+                self.apply(transition);
+                return;
+            }
+        }
+        self.route_actions(key, |s, action| s.action_up(action));
+    }
+
+    /// Route the changed axis positions of the input sources to the topmost scene.
+    ///
+    /// The first source with a connected controller is used for each player, and the axis is
+    /// centered once when the controller is disconnected.
+    fn poll_sources(&mut self) {
+        for (i, player) in [Player::One, Player::Two].into_iter().enumerate() {
+            let axis = self.sources.iter_mut().find_map(|s| s.axis(player));
+            if axis == self.axes[i] {
+                continue;
+            }
+            self.axes[i] = axis;
+            if let Some(s) = self.scenes.last_mut() {
+                let transition = s.axis(player, axis.unwrap_or_default());
+                self.apply(transition);
+            }
+        }
+    }
+
+    /// Route a mouse event at the given output position to the topmost scene in view coordinates.
+    fn mouse(&mut self, x: i32, y: i32, route: impl Fn(&mut dyn Scene, f32, f32) -> Transition) {
+        let (width, height) = self.renderer.size();
+        let position = window_to_view(x, y, width, height, self.renderer.aspect());
+        if let (Some((x, y)), Some(s)) = (position, self.scenes.last_mut()) {
+            let transition = route(s.as_mut(), x, y);
+            self.apply(transition);
+        }
+    }
+
+    /// Route the actions bound to the given key to the topmost scene.
+    ///
+    /// The remaining actions are dropped after a scene change so that a single key press does not
+    /// affect the scene which was just shown.
+    fn route_actions(&mut self, key: Key, route: impl Fn(&mut dyn Scene, Action) -> Transition) {
+        let actions: Vec<Action> = self.context.options.bindings.actions(key).collect();
+        for action in actions {
+            let transition = match self.scenes.last_mut() {
+                Some(s) => route(s.as_mut(), action),
+                None => return,
+            };
+            if !matches!(transition, Transition::Stay) {
+                self.apply(transition);
+                return;
+            }
         }
     }
 
+    /// Capture the current frame of the visible scenes and save it into the given image file.
+    pub fn screenshot(&self, path: &Path) -> io::Result<()> {
+        if !self.running() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "no active scene"));
+        }
+        let (palette, aspect) = (self.renderer.palette(), self.renderer.aspect());
+        screenshot::save_with(path, palette, aspect, |ctx| {
+            draw_scenes(&self.scenes, ctx, 1.0)
+        })
+    }
+
     /// Returns a boolean indicating whether the application has an active scene i.e is running.
     pub fn running(&self) -> bool {
-        self.scene.is_some()
+        !self.scenes.is_empty()
+    }
+
+    /// Apply the given transition requested by the topmost scene to the scene stack.
+    fn apply(&mut self, transition: Transition) {
+        // The sounds are played before the scene which started them may be removed.
+        if let Some(s) = self.scenes.last_mut() {
+            for sound in s.take_sounds() {
+                self.audio.play(sound);
+            }
+        }
+        match transition {
+            Transition::Stay => return,
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop => {
+                self.scenes.pop();
+            }
+            Transition::Replace(scene) => {
+                self.scenes.pop();
+                self.scenes.push(scene);
+            }
+            Transition::Reset(scene) => self.scenes = vec![scene],
+            Transition::Quit => self.scenes.clear(),
+            Transition::Save(options, then) => {
+                self.save(*options);
+                return self.apply(*then);
+            }
+        }
+        // The axes are routed again to the scene which is shown after the change.
+        self.axes = [None; 2];
+        if let Some(s) = self.scenes.last_mut() {
+            s.enter(&self.context);
+        }
+    }
+
+    /// Use the given options in the shared context and save them for the next runs.
+    fn save(&mut self, options: Options) {
+        self.renderer.set_palette(options.theme.palette());
+        if let Err(error) = options.save(&self.options_file) {
+            let path = self.options_file.display();
+            eprintln!("Failed to save options {}: {}", path, error);
+        }
+        self.context.options = options;
+    }
+}
+
+/// Render the topmost scene of the given stack and the scenes which are visible below it.
+///
+/// Only the topmost scene is being updated, so the scenes below it are drawn without interpolation.
+fn draw_scenes(scenes: &[Box<dyn Scene>], ctx: &mut dyn Renderer, alpha: f32) {
+    let bottom = scenes
+        .iter()
+        .rposition(|s| !s.is_overlay())
+        .unwrap_or_default();
+    for (i, s) in scenes.iter().enumerate().skip(bottom) {
+        let alpha = if i + 1 == scenes.len() { alpha } else { 1.0 };
+        s.draw(ctx, alpha);
+    }
+}
+
+/// Build a unique file name for a screenshot which is captured with the hotkey.
+fn screenshot_path() -> PathBuf {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    PathBuf::from(format!("screenshot-{}.png", time.as_millis()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{bindings::Bindings, options::Theme, software::SoftwareRenderer};
+
+    /// The calls which the stub scenes have received in the order they happened.
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    /// A scene which logs its calls and requests a prepared transition when a key is released.
+    struct Stub {
+        name: &'static str,
+        log: Log,
+        overlay: bool,
+        transition: Option<Transition>,
+    }
+
+    impl Stub {
+        fn new(name: &'static str, log: &Log) -> Self {
+            Self {
+                name,
+                log: log.clone(),
+                overlay: false,
+                transition: None,
+            }
+        }
+
+        /// Let the scene be drawn over the scenes below it.
+        fn overlay(mut self) -> Self {
+            self.overlay = true;
+            self
+        }
+
+        /// Request the given transition when the next key is released.
+        fn then(mut self, transition: Transition) -> Self {
+            self.transition = Some(transition);
+            self
+        }
+
+        fn record(&self, call: String) {
+            self.log
+                .borrow_mut()
+                .push(format!("{} {}", self.name, call));
+        }
+    }
+
+    impl Scene for Stub {
+        fn tick(&mut self, _dt: Duration) -> Transition {
+            Transition::Stay
+        }
+
+        fn draw(&self, _ctx: &mut dyn Renderer, alpha: f32) {
+            self.record(format!("draw {}", alpha));
+        }
+
+        fn action_down(&mut self, action: Action) -> Transition {
+            self.record(format!("down {}", action.name()));
+            Transition::Stay
+        }
+
+        fn action_up(&mut self, action: Action) -> Transition {
+            self.record(format!("up {}", action.name()));
+            self.transition.take().unwrap_or(Transition::Stay)
+        }
+
+        fn enter(&mut self, _context: &Context) {
+            self.record("enter".to_string());
+        }
+
+        fn is_overlay(&self) -> bool {
+            self.overlay
+        }
+    }
+
+    /// Build an application which starts from the given scene and keeps its options in the given
+    /// file of the temporary directory.
+    fn app(scene: Stub, options_file: &str) -> App {
+        let path =
+            std::env::temp_dir().join(format!("rust-pong-{}-{}", std::process::id(), options_file));
+        let _ = std::fs::remove_file(&path);
+        let renderer = Box::new(SoftwareRenderer::new(64, 48));
+        App::with_scene(renderer, Tuning::default(), Box::new(scene)).with_options_file(&path)
+    }
+
+    /// Press and release the key which confirms in the menus.
+    fn confirm(app: &mut App) {
+        let key = Bindings::default().key(Action::Confirm);
+        app.key_down(key);
+        app.key_up(key);
+    }
+
+    /// Take the logged calls since the previous call.
+    fn take(log: &Log) -> Vec<String> {
+        std::mem::take(&mut log.borrow_mut())
+    }
+
+    #[test]
+    fn transitions_change_the_scene_stack() {
+        let log = Log::default();
+        let second = Stub::new("b", &log).then(Transition::Replace(Box::new(
+            Stub::new("c", &log).then(Transition::Pop),
+        )));
+        let first = Stub::new("a", &log).then(Transition::Push(Box::new(second)));
+        let mut app = app(first, "stack.toml");
+        take(&log);
+
+        confirm(&mut app);
+        assert_eq!(take(&log), ["a down confirm", "a up confirm", "b enter"]);
+        assert_eq!(app.scenes.len(), 2);
+
+        confirm(&mut app);
+        assert_eq!(take(&log), ["b down confirm", "b up confirm", "c enter"]);
+        assert_eq!(app.scenes.len(), 2);
+
+        // The scene below is entered again when the scene on top of it is removed.
+        confirm(&mut app);
+        assert_eq!(take(&log), ["c down confirm", "c up confirm", "a enter"]);
+        assert_eq!(app.scenes.len(), 1);
+
+        app.apply(Transition::Push(Box::new(Stub::new("d", &log))));
+        app.apply(Transition::Reset(Box::new(Stub::new("e", &log))));
+        assert_eq!(take(&log), ["d enter", "e enter"]);
+        assert_eq!(app.scenes.len(), 1);
+
+        app.apply(Transition::Quit);
+        assert!(!app.running());
+    }
+
+    #[test]
+    fn saved_options_are_shared_and_written() {
+        let log = Log::default();
+        let options = Options {
+            theme: Theme::Amber,
+            ..Options::default()
+        };
+        let save = Transition::Save(Box::new(options), Box::new(Transition::Stay));
+        let mut app = app(Stub::new("a", &log).then(save), "save.toml");
+        assert_eq!(app.context.options.theme, Theme::Classic);
+
+        confirm(&mut app);
+        assert_eq!(app.context.options, options);
+        assert_eq!(app.renderer.palette(), Theme::Amber.palette());
+        assert_eq!(Options::load(&app.options_file).unwrap(), options);
+        let _ = std::fs::remove_file(&app.options_file);
+    }
+
+    #[test]
+    fn input_is_routed_to_the_topmost_scene() {
+        let log = Log::default();
+        let mut app = app(Stub::new("a", &log), "input.toml");
+        app.apply(Transition::Push(Box::new(Stub::new("b", &log))));
+        take(&log);
+
+        // The key which pauses the match also goes back in the menus.
+        let key = Bindings::default().key(Action::Pause);
+        app.key_down(key);
+        app.key_up(key);
+        let actions = ["b down pause", "b down back", "b up pause", "b up back"];
+        assert_eq!(take(&log), actions);
+    }
+
+    #[test]
+    fn overlays_are_drawn_over_the_scenes_below_them() {
+        let log = Log::default();
+        let mut app = app(Stub::new("a", &log), "draw.toml");
+        app.apply(Transition::Push(Box::new(Stub::new("b", &log))));
+        app.apply(Transition::Push(Box::new(Stub::new("c", &log).overlay())));
+        take(&log);
+
+        // The scenes below the topmost scene are drawn without interpolation.
+        draw_scenes(&app.scenes, app.renderer.as_mut(), 0.5);
+        assert_eq!(take(&log), ["b draw 1", "c draw 0.5"]);
     }
 }
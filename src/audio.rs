@@ -0,0 +1,238 @@
+use std::f32::consts::TAU;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::events::GameEvent;
+
+/// The sample rate of the synthesized sounds in samples per second.
+pub const SAMPLE_RATE: u32 = 44_100;
+
+/// The loudness of the synthesized sounds as a fraction of the full range.
+const VOLUME: f32 = 0.25;
+
+/// The duration of the fade at both ends of each tone which avoids clicks.
+const FADE: Duration = Duration::from_millis(2);
+
+/// A sound effect of the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sound {
+    /// The ball bounces from a paddle.
+    PaddleHit,
+    /// The ball bounces from the top or the bottom wall.
+    WallBounce,
+    /// The ball enters a goal.
+    Score,
+    /// The selection of a menu moves to another item.
+    MenuMove,
+    /// The selected item of a menu is confirmed.
+    MenuSelect,
+    /// The match is over.
+    GameOver,
+}
+
+impl Sound {
+    /// All sound effects of the game.
+    pub const ALL: [Sound; 6] = [
+        Sound::PaddleHit,
+        Sound::WallBounce,
+        Sound::Score,
+        Sound::MenuMove,
+        Sound::MenuSelect,
+        Sound::GameOver,
+    ];
+
+    /// Get the sound which is played for the given event of the match if any.
+    ///
+    /// The end of the match is left silent as the end game scene plays its own jingle.
+    pub fn of_event(event: &GameEvent) -> Option<Sound> {
+        match event {
+            GameEvent::PaddleHit { .. } => Some(Sound::PaddleHit),
+            GameEvent::WallBounce => Some(Sound::WallBounce),
+            GameEvent::Goal { .. } => Some(Sound::Score),
+            GameEvent::MatchEnded | GameEvent::ServeStarted => None,
+        }
+    }
+
+    /// Get the tones of the sound as their frequencies in hertz and durations in milliseconds.
+    ///
+    /// The blips of the court follow the pitches of the original arcade game.
+    fn tones(&self) -> &'static [(f32, u64)] {
+        match self {
+            Sound::PaddleHit => &[(459.0, 40)],
+            Sound::WallBounce => &[(226.0, 30)],
+            Sound::Score => &[(490.0, 257)],
+            Sound::MenuMove => &[(660.0, 25)],
+            Sound::MenuSelect => &[(880.0, 60)],
+            Sound::GameOver => &[(523.0, 150), (392.0, 150), (330.0, 150), (262.0, 400)],
+        }
+    }
+}
+
+/// Synthesize the samples of the given sound as square waves with the given sample rate.
+pub fn synthesize(sound: Sound, sample_rate: u32) -> Vec<i16> {
+    let mut samples = Vec::new();
+    let fade = samples_in(FADE, sample_rate).max(1) as f32;
+    for &(frequency, millis) in sound.tones() {
+        let length = samples_in(Duration::from_millis(millis), sample_rate);
+        for i in 0..length {
+            let phase = (i as f32 * frequency / sample_rate as f32 * TAU).sin();
+            let envelope = (i as f32 / fade).min((length - i) as f32 / fade).min(1.0);
+            let value = phase.signum() * envelope * VOLUME * i16::MAX as f32;
+            samples.push(value as i16);
+        }
+    }
+    samples
+}
+
+/// Write the given mono samples into a 16-bit PCM WAV file.
+pub fn write_wav(writer: &mut impl Write, samples: &[i16], sample_rate: u32) -> io::Result<()> {
+    let data_size = samples.len() as u32 * 2;
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(36 + data_size).to_le_bytes())?;
+    writer.write_all(b"WAVEfmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    // The format is PCM with one channel.
+    writer.write_all(&1u16.to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&(sample_rate * 2).to_le_bytes())?;
+    writer.write_all(&2u16.to_le_bytes())?;
+    writer.write_all(&16u16.to_le_bytes())?;
+    writer.write_all(b"data")?;
+    writer.write_all(&data_size.to_le_bytes())?;
+    for sample in samples {
+        writer.write_all(&sample.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// A destination of the sound effects which are played by the scenes.
+pub trait AudioSink {
+    /// Start playing the given sound.
+    fn play(&mut self, sound: Sound);
+
+    /// Tell the sink that the given amount of game time has passed.
+    ///
+    /// Sinks which play the sounds in real time ignore the game time.
+    fn advance(&mut self, _dt: Duration) {}
+}
+
+/// A sink which only keeps the played sounds, e.g. when there's no audio device.
+#[derive(Clone, Debug, Default)]
+pub struct NullSink {
+    played: Vec<Sound>,
+}
+
+impl NullSink {
+    /// Get the sounds which have been played in the order they were played.
+    pub fn played(&self) -> &[Sound] {
+        &self.played
+    }
+}
+
+impl AudioSink for NullSink {
+    fn play(&mut self, sound: Sound) {
+        self.played.push(sound);
+    }
+}
+
+/// A sink which keeps the played sounds with the steps of the game time when they were played.
+///
+/// Each advance of the game time counts as one step, which lets the tests check the sounds of a
+/// simulated match step by step.
+#[derive(Clone, Debug, Default)]
+pub struct RecordingSink {
+    step: u32,
+    played: Vec<(u32, Sound)>,
+}
+
+impl RecordingSink {
+    /// Get the sounds which have been played with their steps in the order they were played.
+    pub fn played(&self) -> &[(u32, Sound)] {
+        &self.played
+    }
+}
+
+impl AudioSink for RecordingSink {
+    fn play(&mut self, sound: Sound) {
+        self.played.push((self.step, sound));
+    }
+
+    fn advance(&mut self, _dt: Duration) {
+        self.step += 1;
+    }
+}
+
+/// A sink which mixes the played sounds on the game timeline to be saved into a WAV file.
+#[derive(Clone, Debug)]
+pub struct WavSink {
+    sample_rate: u32,
+    samples: Vec<i16>,
+    /// The game time from the start of the timeline.
+    time: Duration,
+    played: Vec<(Duration, Sound)>,
+}
+
+impl WavSink {
+    /// Build a new empty timeline with the given sample rate.
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            samples: Vec::new(),
+            time: Duration::ZERO,
+            played: Vec::new(),
+        }
+    }
+
+    /// Get the sounds which have been played with their game times.
+    pub fn played(&self) -> &[(Duration, Sound)] {
+        &self.played
+    }
+
+    /// Get the mixed samples of the timeline.
+    pub fn samples(&self) -> &[i16] {
+        &self.samples
+    }
+
+    /// Save the mixed timeline into the WAV file at the given path.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        write_wav(&mut writer, &self.samples, self.sample_rate)?;
+        writer.flush()
+    }
+}
+
+impl Default for WavSink {
+    fn default() -> Self {
+        Self::new(SAMPLE_RATE)
+    }
+}
+
+impl AudioSink for WavSink {
+    fn play(&mut self, sound: Sound) {
+        self.played.push((self.time, sound));
+        let start = samples_in(self.time, self.sample_rate);
+        let sound = synthesize(sound, self.sample_rate);
+        if self.samples.len() < start + sound.len() {
+            self.samples.resize(start + sound.len(), 0);
+        }
+        for (mixed, sample) in self.samples[start..].iter_mut().zip(sound) {
+            *mixed = mixed.saturating_add(sample);
+        }
+    }
+
+    fn advance(&mut self, dt: Duration) {
+        self.time += dt;
+        let length = samples_in(self.time, self.sample_rate);
+        if self.samples.len() < length {
+            self.samples.resize(length, 0);
+        }
+    }
+}
+
+/// Get the amount of samples in the given duration with the given sample rate.
+fn samples_in(duration: Duration, sample_rate: u32) -> usize {
+    (duration.as_nanos() * sample_rate as u128 / 1_000_000_000) as usize
+}
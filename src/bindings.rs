@@ -0,0 +1,98 @@
+use crate::input::{Action, Key, Player};
+
+/// The key which is reserved for capturing screenshots and cannot be bound to an action.
+pub const RESERVED_KEY: Key = Key::F12;
+
+/// The keys which are bound to the actions of the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bindings {
+    /// The bound keys in the order of the actions in `Action::ALL`.
+    keys: [Key; Action::ALL.len()],
+}
+
+impl Bindings {
+    /// Get the key which is bound to the given action.
+    pub fn key(&self, action: Action) -> Key {
+        self.keys[index(action)]
+    }
+
+    /// Get the actions which are bound to the given key in the order of `Action::ALL`.
+    pub fn actions(&self, key: Key) -> impl Iterator<Item = Action> + '_ {
+        Action::ALL
+            .into_iter()
+            .filter(move |action| self.key(*action) == key)
+    }
+
+    /// Find another action of the same kind which is already bound to the given key.
+    pub fn conflict(&self, action: Action, key: Key) -> Option<Action> {
+        self.actions(key)
+            .find(|other| *other != action && other.is_menu() == action.is_menu())
+    }
+
+    /// Bind the given key to the given action unless it would conflict with another action.
+    ///
+    /// The conflicting action is returned as the error and the bindings are left untouched.
+    pub fn bind(&mut self, action: Action, key: Key) -> Result<(), Action> {
+        match self.conflict(action, key) {
+            Some(other) => Err(other),
+            None => {
+                self.set(action, key);
+                Ok(())
+            }
+        }
+    }
+
+    /// Bind the given key to the given action without checking for conflicts.
+    pub fn set(&mut self, action: Action, key: Key) {
+        self.keys[index(action)] = key;
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            keys: Action::ALL.map(|action| match action {
+                Action::MoveUp(Player::One) => Key::W,
+                Action::MoveDown(Player::One) => Key::S,
+                Action::MoveUp(Player::Two) => Key::Up,
+                Action::MoveDown(Player::Two) => Key::Down,
+                Action::Pause => Key::Escape,
+                Action::MenuUp => Key::Up,
+                Action::MenuDown => Key::Down,
+                Action::MenuLeft => Key::Left,
+                Action::MenuRight => Key::Right,
+                Action::Confirm => Key::Enter,
+                Action::Back => Key::Escape,
+            }),
+        }
+    }
+}
+
+/// Get the position of the given action in `Action::ALL`.
+fn index(action: Action) -> usize {
+    Action::ALL
+        .iter()
+        .position(|a| *a == action)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflicting_key_is_refused_within_the_same_kind() {
+        let mut bindings = Bindings::default();
+        let up = Action::MoveUp(Player::One);
+        assert_eq!(
+            bindings.bind(up, Key::S),
+            Err(Action::MoveDown(Player::One))
+        );
+        assert_eq!(bindings, Bindings::default());
+
+        // A key may be bound to both a match action and a menu action.
+        assert_eq!(bindings.bind(up, Key::Left), Ok(()));
+        let actions: Vec<_> = bindings.actions(Key::Left).collect();
+        assert_eq!(actions, [up, Action::MenuLeft]);
+    }
+}
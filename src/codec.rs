@@ -0,0 +1,29 @@
+use std::io::{self, Read};
+
+/// Read a fixed amount of bytes from the given reader.
+pub fn read_bytes<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Read a number which must be finite.
+pub fn read_f32(reader: &mut impl Read) -> io::Result<f32> {
+    let value = f32::from_le_bytes(read_bytes(reader)?);
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(invalid("invalid number"))
+    }
+}
+
+/// Append the bytes which the given function writes to the end of the given buffer.
+pub fn append(buffer: &mut Vec<u8>, write: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) {
+    // Writing into a vector never fails.
+    write(buffer).expect("writing into a vector failed");
+}
+
+/// Build an error which describes invalid binary data.
+pub fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
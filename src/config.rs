@@ -0,0 +1,213 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// A value of a key in a configuration file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::Float(value) if value.fract() == 0.0 => write!(f, "{:.1}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "\"{}\"", value),
+        }
+    }
+}
+
+/// A key and its value in a configuration file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    /// The name of the section where the key is defined or an empty name for the top level keys.
+    pub section: String,
+    pub key: String,
+    pub value: Value,
+    /// The line number where the key is defined starting from one.
+    pub line: usize,
+}
+
+impl Entry {
+    /// Get the value as a boolean.
+    pub fn as_bool(&self) -> Result<bool, Error> {
+        match self.value {
+            Value::Bool(value) => Ok(value),
+            _ => Err(self.error("expected true or false")),
+        }
+    }
+
+    /// Get the value as an integer in the given inclusive range.
+    pub fn as_integer(&self, min: i64, max: i64) -> Result<i64, Error> {
+        match self.value {
+            Value::Integer(value) if (min..=max).contains(&value) => Ok(value),
+            Value::Integer(_) => {
+                Err(self.error(format!("expected a value from {} to {}", min, max)))
+            }
+            _ => Err(self.error("expected an integer")),
+        }
+    }
+
+    /// Get the value as a number in the given inclusive range.
+    pub fn as_float(&self, range: RangeInclusive<f32>) -> Result<f32, Error> {
+        let value = match self.value {
+            Value::Integer(value) => value as f32,
+            Value::Float(value) => value as f32,
+            _ => return Err(self.error("expected a number")),
+        };
+        if !range.contains(&value) {
+            return Err(self.error(format!(
+                "expected a number from {} to {}",
+                range.start(),
+                range.end()
+            )));
+        }
+        Ok(value)
+    }
+
+    /// Get the value as a string.
+    pub fn as_str(&self) -> Result<&str, Error> {
+        match &self.value {
+            Value::String(value) => Ok(value),
+            _ => Err(self.error("expected a quoted string")),
+        }
+    }
+
+    /// Get the name of the key with its section e.g. `physics.nudge`.
+    pub fn name(&self) -> String {
+        match self.section.as_str() {
+            "" => self.key.clone(),
+            section => format!("{}.{}", section, self.key),
+        }
+    }
+
+    /// Build an error about the value of the entry.
+    pub fn error(&self, message: impl fmt::Display) -> Error {
+        Error {
+            line: self.line,
+            message: format!("invalid value for {}: {}", self.name(), message),
+        }
+    }
+
+    /// Build an error about a key which is not known as the given kind of setting.
+    pub fn unknown(&self, kind: &str) -> Error {
+        Error {
+            line: self.line,
+            message: format!("unknown {} '{}'", kind, self.name()),
+        }
+    }
+}
+
+/// An error in the contents of a configuration file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    /// The line number where the error was found starting from one.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Parse the entries of a configuration file written in a subset of TOML.
+///
+/// Supported syntax is `[section]` headers, `key = value` pairs and `#` comments, where the value is
+/// a boolean, an integer, a float or a double quoted string without escapes.
+pub fn parse(source: &str) -> Result<Vec<Entry>, Error> {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut section = String::new();
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |message: String| Error {
+            line: line_number,
+            message,
+        };
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[') {
+            let name = header
+                .strip_suffix(']')
+                .ok_or_else(|| error("missing ] after the section name".to_string()))?
+                .trim();
+            if !is_identifier(name) {
+                return Err(error(format!("invalid section name '{}'", name)));
+            }
+            section = name.to_string();
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| error(format!("expected key = value but found '{}'", line)))?;
+        let key = key.trim();
+        if !is_identifier(key) {
+            return Err(error(format!("invalid key name '{}'", key)));
+        }
+        if entries.iter().any(|e| e.section == section && e.key == key) {
+            return Err(error(format!("duplicate key '{}'", key)));
+        }
+        entries.push(Entry {
+            section: section.clone(),
+            key: key.to_string(),
+            value: parse_value(value.trim()).map_err(error)?,
+            line: line_number,
+        });
+    }
+    Ok(entries)
+}
+
+/// Remove the comment from the given line while ignoring the comment character inside strings.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => (),
+        }
+    }
+    line
+}
+
+/// Check whether the given text is a valid bare key or section name.
+fn is_identifier(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Parse the given value text into a typed value.
+fn parse_value(text: &str) -> Result<Value, String> {
+    if let Some(quoted) = text.strip_prefix('"') {
+        return match quoted.strip_suffix('"') {
+            Some(value) if !value.contains('"') => Ok(Value::String(value.to_string())),
+            _ => Err(format!("invalid string {}", text)),
+        };
+    }
+    match text {
+        "true" => return Ok(Value::Bool(true)),
+        "false" => return Ok(Value::Bool(false)),
+        "" => return Err("missing value".to_string()),
+        _ => (),
+    }
+    let number = text.replace('_', "");
+    if let Ok(value) = number.parse() {
+        return Ok(Value::Integer(value));
+    }
+    match number.parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(Value::Float(value)),
+        _ => Err(format!("invalid value '{}' (strings must be quoted)", text)),
+    }
+}
@@ -0,0 +1,11 @@
+use crate::{options::Options, tuning::Tuning};
+
+/// The settings of the application which are shared with its scenes.
+///
+/// The application loads the context once and shows it to the topmost scene, while the scenes
+/// return their changes to it with a transition.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Context {
+    pub options: Options,
+    pub tuning: Tuning,
+}
@@ -0,0 +1,106 @@
+use crate::input::Player;
+
+/// The default dead zone of the analog sticks as a fraction of their full range.
+pub const DEAD_ZONE: f32 = 0.24;
+
+/// A source of analog input for the paddles which is polled before each simulation step.
+pub trait InputSource {
+    /// Get the position of the vertical axis of the given player in range [-1, 1].
+    ///
+    /// Negative values move the paddle up and nothing is returned when the player has no controller.
+    fn axis(&mut self, player: Player) -> Option<f32>;
+}
+
+/// A controller whose stick positions are set by the program instead of real hardware.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SimulatedController {
+    /// The raw stick positions of the players or nothing for a disconnected controller.
+    axes: [Option<f32>; 2],
+    dead_zone: f32,
+}
+
+impl SimulatedController {
+    /// Build a new controller without connected sticks with the given dead zone.
+    pub fn new(dead_zone: f32) -> Self {
+        Self {
+            axes: [None; 2],
+            dead_zone,
+        }
+    }
+
+    /// Move the stick of the given player into the given raw position.
+    pub fn set_axis(&mut self, player: Player, value: f32) {
+        self.axes[index(player)] = Some(value);
+    }
+
+    /// Remove the stick of the given player.
+    pub fn disconnect(&mut self, player: Player) {
+        self.axes[index(player)] = None;
+    }
+}
+
+impl Default for SimulatedController {
+    fn default() -> Self {
+        Self::new(DEAD_ZONE)
+    }
+}
+
+impl InputSource for SimulatedController {
+    fn axis(&mut self, player: Player) -> Option<f32> {
+        let value = self.axes[index(player)]?;
+        Some(apply_dead_zone(value, self.dead_zone))
+    }
+}
+
+/// Ignore the given amount of the axis around its center and rescale the rest into range [-1, 1].
+///
+/// The rescaling keeps the movement continuous when the axis leaves the dead zone.
+pub fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    let dead_zone = dead_zone.clamp(0.0, 0.99);
+    let magnitude = value.abs().min(1.0);
+    if value.is_nan() || magnitude <= dead_zone {
+        return 0.0;
+    }
+    value.signum() * (magnitude - dead_zone) / (1.0 - dead_zone)
+}
+
+/// Get the index of the axis of the given player.
+fn index(player: Player) -> usize {
+    match player {
+        Player::One => 0,
+        Player::Two => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dead_zone_ignores_small_deflections() {
+        assert_eq!(apply_dead_zone(0.2, 0.25), 0.0);
+        assert_eq!(apply_dead_zone(-0.25, 0.25), 0.0);
+        assert_eq!(apply_dead_zone(f32::NAN, 0.25), 0.0);
+    }
+
+    #[test]
+    fn dead_zone_rescales_the_rest_of_the_range() {
+        assert_eq!(apply_dead_zone(0.625, 0.25), 0.5);
+        assert_eq!(apply_dead_zone(-0.625, 0.25), -0.5);
+        assert_eq!(apply_dead_zone(1.0, 0.25), 1.0);
+        assert_eq!(apply_dead_zone(-3.0, 0.25), -1.0);
+        assert!(apply_dead_zone(0.2501, 0.25) < 0.001);
+    }
+
+    #[test]
+    fn simulated_controller_applies_its_dead_zone() {
+        let mut controller = SimulatedController::new(0.25);
+        assert_eq!(controller.axis(Player::One), None);
+        controller.set_axis(Player::One, -0.625);
+        controller.set_axis(Player::Two, 0.1);
+        assert_eq!(controller.axis(Player::One), Some(-0.5));
+        assert_eq!(controller.axis(Player::Two), Some(0.0));
+        controller.disconnect(Player::One);
+        assert_eq!(controller.axis(Player::One), None);
+    }
+}
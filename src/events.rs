@@ -0,0 +1,91 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::scenes::Side;
+
+/// An event of the match which happens on the court.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameEvent {
+    /// The ball bounced from the paddle on the given side with the given speed after the hit.
+    PaddleHit { side: Side, speed: f32 },
+    /// The ball bounced from the top or the bottom wall.
+    WallBounce,
+    /// The player on the given side scored a point.
+    Goal { scorer: Side },
+    /// Either player won the match according to the match rules.
+    MatchEnded,
+    /// The countdown ended and the ball started moving.
+    ServeStarted,
+}
+
+/// A receiver of the events of the match, e.g. for statistics or telemetry.
+pub trait Subscriber {
+    /// Tell the subscriber that the given event happened.
+    fn notify(&mut self, event: &GameEvent);
+}
+
+impl<F: FnMut(&GameEvent)> Subscriber for F {
+    fn notify(&mut self, event: &GameEvent) {
+        self(event)
+    }
+}
+
+/// A bus which passes the events of the match to its subscribers as they happen.
+///
+/// The published events are also kept until they are taken, which lets the scenes poll them. A
+/// cloned bus starts without subscribers and pending events, so a copy of the court which is
+/// simulated again does not notify the subscribers of the original court twice.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Rc<RefCell<dyn Subscriber>>>,
+    /// The published events which have not been taken yet.
+    pending: Vec<GameEvent>,
+}
+
+impl EventBus {
+    /// Notify the given subscriber of the events which are published after this.
+    pub fn subscribe(&mut self, subscriber: Rc<RefCell<dyn Subscriber>>) {
+        self.subscribers.push(subscriber);
+    }
+
+    /// Notify the subscribers of the given event and keep it until the events are taken.
+    pub fn publish(&mut self, event: GameEvent) {
+        for subscriber in &self.subscribers {
+            subscriber.borrow_mut().notify(&event);
+        }
+        self.pending.push(event);
+    }
+
+    /// Take the published events since the events were taken last time.
+    pub fn take(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+impl Clone for EventBus {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloned_bus_does_not_notify_the_original_subscribers() {
+        let count = Rc::new(RefCell::new(0));
+        let counter = count.clone();
+        let mut bus = EventBus::default();
+        bus.subscribe(Rc::new(RefCell::new(move |_: &GameEvent| {
+            *counter.borrow_mut() += 1
+        })));
+        bus.publish(GameEvent::WallBounce);
+
+        let mut clone = bus.clone();
+        clone.publish(GameEvent::WallBounce);
+        assert_eq!(*count.borrow(), 1);
+        assert!(clone.take() == [GameEvent::WallBounce]);
+        assert!(bus.take() == [GameEvent::WallBounce]);
+    }
+}
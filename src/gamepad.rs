@@ -0,0 +1,42 @@
+use windows::Win32::UI::Input::XboxController::{XInputGetState, XINPUT_STATE};
+
+use crate::controller::{apply_dead_zone, InputSource, DEAD_ZONE};
+use crate::input::Player;
+
+/// The return value of XInput functions on success.
+const ERROR_SUCCESS: u32 = 0;
+
+/// An input source which reads the left sticks of the first two XInput controllers.
+pub struct XInputController {
+    dead_zone: f32,
+}
+
+impl XInputController {
+    pub fn new() -> Self {
+        Self {
+            dead_zone: DEAD_ZONE,
+        }
+    }
+}
+
+impl Default for XInputController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputSource for XInputController {
+    fn axis(&mut self, player: Player) -> Option<f32> {
+        let index = match player {
+            Player::One => 0,
+            Player::Two => 1,
+        };
+        let mut state = XINPUT_STATE::default();
+        if unsafe { XInputGetState(index, &mut state) } != ERROR_SUCCESS {
+            return None;
+        }
+        // The stick points up with positive values while the court grows downwards.
+        let value = -(state.Gamepad.sThumbLY as f32 / i16::MAX as f32);
+        Some(apply_dead_zone(value, self.dead_zone))
+    }
+}
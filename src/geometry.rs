@@ -1,4 +1,5 @@
 /// A simple rectangle with a position and size.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Rectangle {
     pub x: f32,
     pub y: f32,
@@ -14,9 +15,109 @@ impl Rectangle {
             && (self.x + self.w) > rect.x
             && (self.y + self.h) > rect.y
     }
+
+    /// Find the first collision when this rectangle moves by the given displacement towards the given rectangle.
+    ///
+    /// Returned hit contains the fraction [0, 1] of the displacement before the rectangles touch and
+    /// the normal of the hit surface. Rectangles which already overlap produce a hit at time zero on
+    /// the axis of the shallowest overlap, unless this rectangle moves out along that axis.
+    pub fn sweep(&self, dx: f32, dy: f32, rect: &Rectangle) -> Option<Hit> {
+        let (x_entry, x_exit) = sweep_axis(self.x, self.w, dx, rect.x, rect.w)?;
+        let (y_entry, y_exit) = sweep_axis(self.y, self.h, dy, rect.y, rect.h)?;
+        let entry = f32::max(x_entry, y_entry);
+        let exit = f32::min(x_exit, y_exit);
+        if entry > exit || entry > 1.0 || exit <= 0.0 {
+            return None;
+        }
+        if entry < 0.0 {
+            return self.push_out(dx, dy, rect);
+        }
+        let (normal_x, normal_y) = if x_entry > y_entry {
+            (-dx.signum(), 0.0)
+        } else {
+            (0.0, -dy.signum())
+        };
+        Some(Hit {
+            time: entry,
+            normal_x,
+            normal_y,
+        })
+    }
+
+    /// Resolve the hit of this rectangle which overlaps the given rectangle while moving by the
+    /// given displacement.
+    ///
+    /// The rectangle leaves along the axis of the shallowest overlap, so a rectangle which is
+    /// squeezed against an edge is not bounced between the surfaces of the other axis.
+    fn push_out(&self, dx: f32, dy: f32, rect: &Rectangle) -> Option<Hit> {
+        let overlap_x = f32::min(self.x + self.w, rect.x + rect.w) - f32::max(self.x, rect.x);
+        let overlap_y = f32::min(self.y + self.h, rect.y + rect.h) - f32::max(self.y, rect.y);
+        // The normal points from the given rectangle towards this one.
+        let center_x = (self.x + self.w / 2.0) - (rect.x + rect.w / 2.0);
+        let center_y = (self.y + self.h / 2.0) - (rect.y + rect.h / 2.0);
+        let horizontal = overlap_x < overlap_y;
+        let (delta, center) = if horizontal {
+            (dx, center_x)
+        } else {
+            (dy, center_y)
+        };
+        if delta == 0.0 || delta.signum() == center.signum() {
+            return None;
+        }
+        let (normal_x, normal_y) = if horizontal {
+            (-delta.signum(), 0.0)
+        } else {
+            (0.0, -delta.signum())
+        };
+        Some(Hit {
+            time: 0.0,
+            normal_x,
+            normal_y,
+        })
+    }
+
+    /// Get a rectangle between this and the given rectangle where alpha zero is this rectangle.
+    pub fn lerp(&self, rect: &Rectangle, alpha: f32) -> Rectangle {
+        Rectangle {
+            x: self.x + (rect.x - self.x) * alpha,
+            y: self.y + (rect.y - self.y) * alpha,
+            w: self.w + (rect.w - self.w) * alpha,
+            h: self.h + (rect.h - self.h) * alpha,
+        }
+    }
+}
+
+/// The time and the surface normal of a collision between a moving and a static rectangle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    pub time: f32,
+    pub normal_x: f32,
+    pub normal_y: f32,
+}
+
+/// Get the fractions of the displacement when the moving span enters and exits the static span.
+///
+/// Returns nothing if the spans never overlap on the axis.
+fn sweep_axis(pos: f32, len: f32, delta: f32, target: f32, target_len: f32) -> Option<(f32, f32)> {
+    if delta > 0.0 {
+        Some((
+            (target - (pos + len)) / delta,
+            (target + target_len - pos) / delta,
+        ))
+    } else if delta < 0.0 {
+        Some((
+            (target + target_len - pos) / delta,
+            (target - (pos + len)) / delta,
+        ))
+    } else if pos < target + target_len && pos + len > target {
+        Some((f32::NEG_INFINITY, f32::INFINITY))
+    } else {
+        None
+    }
 }
 
 /// The pre-defined size for a text object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TextSize {
     Tiny,
     Small,
@@ -24,7 +125,20 @@ pub enum TextSize {
     Big,
 }
 
+impl TextSize {
+    /// Get the font size relative to the height of the view.
+    pub fn scale(&self) -> f32 {
+        match self {
+            TextSize::Tiny => 0.025,
+            TextSize::Small => 0.05,
+            TextSize::Medium => 0.1,
+            TextSize::Big => 0.2,
+        }
+    }
+}
+
 /// A simple text with a position, text and format.
+#[derive(Clone, Debug)]
 pub struct Text {
     pub x: f32,
     pub y: f32,
@@ -38,3 +152,85 @@ impl Text {
         self.text = val.to_string().encode_utf16().collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BALL: Rectangle = Rectangle {
+        x: 0.0,
+        y: 0.0,
+        w: 0.1,
+        h: 0.1,
+    };
+
+    const WALL: Rectangle = Rectangle {
+        x: 0.5,
+        y: -1.0,
+        w: 0.1,
+        h: 2.0,
+    };
+
+    #[test]
+    fn sweep_hits_an_approaching_rectangle() {
+        let hit = BALL.sweep(0.8, 0.0, &WALL).unwrap();
+        assert_eq!(hit.time, 0.5);
+        assert_eq!((hit.normal_x, hit.normal_y), (-1.0, 0.0));
+    }
+
+    #[test]
+    fn sweep_misses_a_rectangle_out_of_reach() {
+        assert_eq!(BALL.sweep(0.3, 0.0, &WALL), None);
+        assert_eq!(BALL.sweep(-0.8, 0.0, &WALL), None);
+    }
+
+    #[test]
+    fn sweep_ignores_an_overlapping_rectangle_when_separating() {
+        let ball = Rectangle { x: 0.55, ..BALL };
+        assert_eq!(ball.sweep(0.1, 0.0, &WALL), None);
+        let ball = Rectangle { x: 0.45, ..BALL };
+        assert_eq!(ball.sweep(-0.1, 0.0, &WALL), None);
+    }
+
+    #[test]
+    fn sweep_hits_an_overlapping_rectangle_when_approaching() {
+        let ball = Rectangle { x: 0.45, ..BALL };
+        let hit = ball.sweep(0.1, 0.0, &WALL).unwrap();
+        assert_eq!(hit.time, 0.0);
+        assert_eq!((hit.normal_x, hit.normal_y), (-1.0, 0.0));
+    }
+
+    #[test]
+    fn sweep_leaves_an_overlap_along_its_shallowest_axis() {
+        // The ball overlaps the left edge of the wall slightly and moves up along it.
+        let ball = Rectangle {
+            x: 0.42,
+            y: 0.5,
+            ..BALL
+        };
+        assert_eq!(ball.sweep(-0.1, -0.1, &WALL), None);
+        let hit = ball.sweep(0.1, -0.1, &WALL).unwrap();
+        assert_eq!((hit.normal_x, hit.normal_y), (-1.0, 0.0));
+    }
+
+    #[test]
+    fn sweep_ignores_a_grazing_rectangle() {
+        let floor = Rectangle {
+            x: -1.0,
+            y: 0.1,
+            w: 2.0,
+            h: 0.1,
+        };
+        assert_eq!(BALL.sweep(0.5, 0.0, &floor), None);
+        let hit = BALL.sweep(0.5, 0.01, &floor).unwrap();
+        assert_eq!(hit.time, 0.0);
+        assert_eq!((hit.normal_x, hit.normal_y), (0.0, -1.0));
+    }
+
+    #[test]
+    fn sweep_without_movement_never_hits() {
+        assert_eq!(BALL.sweep(0.0, 0.0, &WALL), None);
+        let ball = Rectangle { x: 0.55, ..BALL };
+        assert_eq!(ball.sweep(0.0, 0.0, &WALL), None);
+    }
+}
@@ -8,17 +8,16 @@ use windows::Win32::Graphics::DirectWrite::*;
 use windows::Win32::UI::WindowsAndMessaging::GetClientRect;
 
 use crate::geometry::{Rectangle, Text, TextSize};
-use crate::scenes::Scene;
+use crate::renderer::{aspect_offset, Color, Palette, Renderer, ASPECT};
 
-/// A constant for the view aspect ratio.
-const ASPECT: f32 = 1.3;
-
-/// A context object for graphics operations.
+/// A Direct2D based rendering context which draws on the given window.
 pub struct Graphics {
     hwnd: HWND,
     factory: ID2D1Factory1,
     target: Option<ID2D1HwndRenderTarget>,
     brush: Option<ID2D1SolidColorBrush>,
+    palette: Palette,
+    aspect: f32,
     transform: Matrix3x2,
     big_text_format: IDWriteTextFormat,
     medium_text_format: IDWriteTextFormat,
@@ -33,32 +32,108 @@ impl Graphics {
             factory: create_factory()?,
             target: None,
             brush: None,
-            transform: create_aspect_transform(hwnd),
-            big_text_format: create_text_format(0.2),
-            medium_text_format: create_text_format(0.1),
-            small_text_format: create_text_format(0.05),
-            tiny_text_format: create_text_format(0.025),
+            palette: Palette::default(),
+            aspect: ASPECT,
+            transform: create_aspect_transform(hwnd, ASPECT),
+            big_text_format: create_text_format(TextSize::Big.scale()),
+            medium_text_format: create_text_format(TextSize::Medium.scale()),
+            small_text_format: create_text_format(TextSize::Small.scale()),
+            tiny_text_format: create_text_format(TextSize::Tiny.scale()),
         })
     }
 
-    pub fn draw(&mut self, scene: &dyn Scene) {
+    /// Create rendering target and related items. This function should be used
+    /// during the first draw or when the render target should be re-created.
+    fn create_target(&mut self) -> Result<()> {
+        unsafe {
+            let target = self.factory.CreateHwndRenderTarget(
+                &D2D1_RENDER_TARGET_PROPERTIES::default(),
+                &D2D1_HWND_RENDER_TARGET_PROPERTIES {
+                    hwnd: self.hwnd,
+                    pixelSize: get_window_size(self.hwnd),
+                    ..Default::default()
+                },
+            )?;
+            let brush = target.CreateSolidColorBrush(
+                &D2D1_COLOR_F {
+                    r: 1.0,
+                    g: 1.0,
+                    b: 1.0,
+                    a: 1.0,
+                },
+                None,
+            )?;
+            self.target = Some(target);
+            self.brush = Some(brush);
+        }
+        Ok(())
+    }
+
+    /// Release rendering target and related items. These will be automatically
+    /// re-created during the next time the draw function is being called.
+    fn release_target(&mut self) {
+        self.target = None;
+        self.brush = None;
+    }
+
+    /// Rebuild the text formats based on the current window size.
+    fn rebuild_text_formats(&mut self) {
+        if let Some(ctx) = self.target.as_ref() {
+            let size = get_window_size(unsafe { ctx.GetHwnd() });
+            let offset = get_aspect_offset(&size, self.aspect);
+            let scalar = size.height as f32 - offset.Y * 2.0;
+
+            self.tiny_text_format = create_text_format(TextSize::Tiny.scale() * scalar);
+            self.small_text_format = create_text_format(TextSize::Small.scale() * scalar);
+            self.medium_text_format = create_text_format(TextSize::Medium.scale() * scalar);
+            self.big_text_format = create_text_format(TextSize::Big.scale() * scalar);
+        }
+    }
+}
+
+impl Renderer for Graphics {
+    fn clear(&mut self) {
         if self.target.is_none() {
             self.create_target().unwrap();
             self.rebuild_text_formats();
         }
         if let Some(ctx) = self.target.as_ref() {
             unsafe { ctx.BeginDraw() };
-            unsafe { ctx.Clear(Some(&D2D1_COLOR_F::default())) };
-            scene.draw(self);
-            if let Err(error) = unsafe { ctx.EndDraw(None, None) } {
-                if error.code() == D2DERR_RECREATE_TARGET {
-                    self.release_target();
-                }
-            }
+            unsafe { ctx.Clear(Some(&color_f(self.palette.background))) };
+        }
+        self.set_color(self.palette.foreground);
+    }
+
+    fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    fn palette(&self) -> Palette {
+        self.palette
+    }
+
+    fn set_aspect(&mut self, aspect: f32) {
+        self.aspect = aspect;
+        self.transform = create_aspect_transform(self.hwnd, aspect);
+        self.rebuild_text_formats();
+    }
+
+    fn aspect(&self) -> f32 {
+        self.aspect
+    }
+
+    fn size(&self) -> (u32, u32) {
+        let size = get_window_size(self.hwnd);
+        (size.width, size.height)
+    }
+
+    fn set_color(&mut self, color: Color) {
+        if let Some(brush) = self.brush.as_ref() {
+            unsafe { brush.SetColor(&color_f(color)) };
         }
     }
 
-    pub fn draw_rectangle(&self, rectangle: &Rectangle) {
+    fn draw_rectangle(&mut self, rectangle: &Rectangle) {
         let transform = Matrix3x2::translation(rectangle.x, rectangle.y);
         let rect = D2D_RECT_F {
             right: rectangle.w,
@@ -73,7 +148,7 @@ impl Graphics {
         }
     }
 
-    pub fn draw_text(&self, text: &Text) {
+    fn draw_text(&mut self, text: &Text) {
         let format = match text.size {
             TextSize::Tiny => &self.tiny_text_format,
             TextSize::Small => &self.small_text_format,
@@ -82,7 +157,7 @@ impl Graphics {
         };
         if let Some(ctx) = self.target.as_ref() {
             let size = get_window_size(unsafe { ctx.GetHwnd() });
-            let offset = get_aspect_offset(&size);
+            let offset = get_aspect_offset(&size, self.aspect);
             let transform = Matrix3x2::translation(
                 offset.X + text.x * (size.width as f32 - offset.X * 2.0),
                 offset.Y + text.y * (size.height as f32 - offset.Y * 2.0),
@@ -103,10 +178,19 @@ impl Graphics {
         }
     }
 
-    /// Resize the graphics by changing the size of the render target.
-    pub fn resize(&mut self) {
+    fn present(&mut self) {
+        if let Some(ctx) = self.target.as_ref() {
+            if let Err(error) = unsafe { ctx.EndDraw(None, None) } {
+                if error.code() == D2DERR_RECREATE_TARGET {
+                    self.release_target();
+                }
+            }
+        }
+    }
+
+    fn resize(&mut self) {
         if self.target.is_some() {
-            self.transform = create_aspect_transform(self.hwnd);
+            self.transform = create_aspect_transform(self.hwnd, self.aspect);
             if let Some(ctx) = self.target.as_ref() {
                 let hwnd = unsafe { ctx.GetHwnd() };
                 let size = get_window_size(hwnd);
@@ -115,54 +199,6 @@ impl Graphics {
             self.rebuild_text_formats();
         }
     }
-
-    /// Create rendering target and related items. This function should be used
-    /// during the first draw or when the render target should be re-created.
-    fn create_target(&mut self) -> Result<()> {
-        unsafe {
-            let target = self.factory.CreateHwndRenderTarget(
-                &D2D1_RENDER_TARGET_PROPERTIES::default(),
-                &D2D1_HWND_RENDER_TARGET_PROPERTIES {
-                    hwnd: self.hwnd,
-                    pixelSize: get_window_size(self.hwnd),
-                    ..Default::default()
-                },
-            )?;
-            let brush = target.CreateSolidColorBrush(
-                &D2D1_COLOR_F {
-                    r: 1.0,
-                    g: 1.0,
-                    b: 1.0,
-                    a: 1.0,
-                },
-                None,
-            )?;
-            self.target = Some(target);
-            self.brush = Some(brush);
-        }
-        Ok(())
-    }
-
-    /// Release rendering target and related items. These will be automatically
-    /// re-created during the next time the draw function is being called.
-    fn release_target(&mut self) {
-        self.target = None;
-        self.brush = None;
-    }
-
-    /// Rebuild the text formats based on the current window size.
-    fn rebuild_text_formats(&mut self) {
-        if let Some(ctx) = self.target.as_ref() {
-            let size = get_window_size(unsafe { ctx.GetHwnd() });
-            let offset = get_aspect_offset(&size);
-            let scalar = size.height as f32 - offset.Y * 2.0;
-
-            self.tiny_text_format = create_text_format(0.025 * scalar);
-            self.small_text_format = create_text_format(0.05 * scalar);
-            self.medium_text_format = create_text_format(0.1 * scalar);
-            self.big_text_format = create_text_format(0.2 * scalar);
-        }
-    }
 }
 
 /// Construct a new Direct2D factory used to build Direct2D specific items.
@@ -184,26 +220,16 @@ fn get_window_size(hwnd: HWND) -> D2D_SIZE_U {
     }
 }
 
-/// Get the aspect ratio specific offset for the given window size.
-fn get_aspect_offset(size: &D2D_SIZE_U) -> Vector2 {
-    let mut result = Vector2::default();
-    let x = size.width as f32;
-    let y = size.height as f32;
-    let aspect = x / y;
-    if (aspect - ASPECT).abs() > 0.0 {
-        if aspect > ASPECT {
-            result.X = (x - y * ASPECT) / 2.0;
-        } else {
-            result.Y = (y - x / ASPECT) / 2.0;
-        }
-    }
-    result
+/// Get the offset for a view with the given aspect ratio inside the given window size.
+fn get_aspect_offset(size: &D2D_SIZE_U, aspect: f32) -> Vector2 {
+    let (x, y) = aspect_offset(size.width, size.height, aspect);
+    Vector2 { X: x, Y: y }
 }
 
-/// Create a transform matrix for the given window based on the static aspect ratio.
-fn create_aspect_transform(hwnd: HWND) -> Matrix3x2 {
+/// Create a transform matrix for the given window based on the given aspect ratio.
+fn create_aspect_transform(hwnd: HWND, aspect: f32) -> Matrix3x2 {
     let size = get_window_size(hwnd);
-    let offset = get_aspect_offset(&size);
+    let offset = get_aspect_offset(&size, aspect);
     let translation = Matrix3x2::translation(offset.X, offset.Y);
     let scale = Matrix3x2 {
         M11: (size.width as f32 - offset.X * 2.0),
@@ -240,3 +266,13 @@ fn create_text_format(size: f32) -> IDWriteTextFormat {
         text_format
     }
 }
+
+/// Convert the given color into a Direct2D color.
+fn color_f(color: Color) -> D2D1_COLOR_F {
+    D2D1_COLOR_F {
+        r: color.r,
+        g: color.g,
+        b: color.b,
+        a: color.a,
+    }
+}
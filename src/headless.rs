@@ -0,0 +1,247 @@
+use std::cell::RefCell;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::{
+    audio::{AudioSink, Sound},
+    controller::InputSource,
+    events::Subscriber,
+    input::{ActionEvent, Player},
+    random::Random,
+    recording::Recording,
+    renderer::{Palette, ASPECT},
+    rollback::{Loopback, Session},
+    scenes::{Court, MatchSettings, Mode, Scene},
+    screenshot,
+    timer::STEP,
+};
+
+/// An action event which is scheduled to occur at the given simulation time.
+#[derive(Clone, Copy, Debug)]
+pub struct ScriptedInput {
+    pub time: Duration,
+    pub event: ActionEvent,
+}
+
+/// The results of a match which was run with a headless simulation.
+#[derive(Clone, Copy, Debug)]
+pub struct MatchResult {
+    pub l_points: u8,
+    pub r_points: u8,
+    pub l_sets: u8,
+    pub r_sets: u8,
+    pub duration: Duration,
+    pub finished: bool,
+}
+
+/// The results of a match which was run between two rollback sessions.
+#[derive(Clone, Copy, Debug)]
+pub struct RollbackResult {
+    pub result: MatchResult,
+    /// The amount of rollbacks of the first and the second peer.
+    pub rollbacks: (u32, u32),
+    /// The checksums of the confirmed courts of the first and the second peer.
+    pub checksums: (u64, u64),
+    /// Whether both peers ended up with the same court and the same recording.
+    ///
+    /// The courts are only comparable when the match is finished as they stop changing after it.
+    pub converged: bool,
+}
+
+/// A simulation which runs the court without a window, graphics or a message loop.
+pub struct Simulation {
+    court: Court,
+    script: Vec<ScriptedInput>,
+    cursor: usize,
+    time: Duration,
+    step: Duration,
+    audio: Option<Rc<RefCell<dyn AudioSink>>>,
+    palette: Palette,
+    aspect: f32,
+}
+
+impl Simulation {
+    /// Build a new simulation of a match which applies the given scripted inputs at their scheduled times.
+    pub fn new(settings: MatchSettings, mut script: Vec<ScriptedInput>) -> Self {
+        script.sort_by_key(|input| input.time);
+        Self {
+            court: Court::new(settings),
+            script,
+            cursor: 0,
+            time: Duration::ZERO,
+            step: STEP,
+            audio: None,
+            palette: Palette::default(),
+            aspect: ASPECT,
+        }
+    }
+
+    /// Build a new simulation which plays back the match from the given recording.
+    pub fn from_recording(recording: &Recording) -> Self {
+        let script = recording
+            .inputs
+            .iter()
+            .map(|input| ScriptedInput {
+                time: STEP * input.step,
+                event: input.event,
+            })
+            .collect();
+        Self::new(recording.settings, script)
+    }
+
+    /// Use the given duration as the length of each simulation step.
+    pub fn with_step(mut self, step: Duration) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Capture the frames with the given colors and view aspect ratio.
+    pub fn with_view(mut self, palette: Palette, aspect: f32) -> Self {
+        self.palette = palette;
+        self.aspect = aspect;
+        self
+    }
+
+    /// Play the sounds of the court into the given sink and advance it with each simulated step.
+    pub fn with_audio(mut self, audio: Rc<RefCell<dyn AudioSink>>) -> Self {
+        self.audio = Some(audio);
+        self
+    }
+
+    /// Notify the given subscriber of the events of the simulated match.
+    pub fn with_subscriber(mut self, subscriber: Rc<RefCell<dyn Subscriber>>) -> Self {
+        self.court.subscribe(subscriber);
+        self
+    }
+
+    /// Get the court which is being simulated.
+    pub fn court(&self) -> &Court {
+        &self.court
+    }
+
+    /// Get the amount of simulated time since the start of the simulation.
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Capture the current frame of the court and save it into the given image file.
+    pub fn screenshot(&self, path: &Path) -> io::Result<()> {
+        screenshot::save_with(path, self.palette, self.aspect, |ctx| {
+            self.court.draw(ctx, 1.0)
+        })
+    }
+
+    /// Apply the axes of the given input source to the court before the next step.
+    pub fn poll(&mut self, source: &mut dyn InputSource) {
+        for player in [Player::One, Player::Two] {
+            if let Some(value) = source.axis(player) {
+                self.court.steer(player, value);
+            }
+        }
+    }
+
+    /// Apply the scripted inputs which are due and advance the simulation by one step.
+    pub fn step(&mut self) {
+        while let Some(input) = self.script.get(self.cursor) {
+            if input.time > self.time {
+                break;
+            }
+            match input.event {
+                ActionEvent::Pressed(action) => self.court.press(action),
+                ActionEvent::Released(action) => self.court.release(action),
+                ActionEvent::Aimed(player, y) => self.court.aim(player, y),
+                ActionEvent::Steered(player, value) => self.court.steer(player, value),
+            }
+            self.cursor += 1;
+        }
+        self.court.update(self.step);
+        let events = self.court.take_events();
+        if let Some(audio) = &self.audio {
+            let mut audio = audio.borrow_mut();
+            for sound in events.iter().filter_map(Sound::of_event) {
+                audio.play(sound);
+            }
+            audio.advance(self.step);
+        }
+        self.time += self.step;
+    }
+
+    /// Run the simulation until the match is over or the given time limit is reached.
+    pub fn run(&mut self, limit: Duration) -> MatchResult {
+        while !self.court.is_over() && self.time < limit {
+            self.step();
+        }
+        let (l_points, r_points) = self.court.points();
+        let (l_sets, r_sets) = self.court.sets();
+        MatchResult {
+            l_points,
+            r_points,
+            l_sets,
+            r_sets,
+            duration: self.time,
+            finished: self.court.is_over(),
+        }
+    }
+}
+
+/// Run a two player match between two rollback sessions which are connected with a loopback
+/// transport with the given latency and jitter.
+///
+/// The players hold random movements for random durations, so that the sessions have to roll back
+/// whenever a new movement arrives late.
+pub fn run_rollback(
+    settings: MatchSettings,
+    latency: Duration,
+    jitter: Duration,
+    limit: Duration,
+) -> RollbackResult {
+    let settings = MatchSettings {
+        mode: Mode::TwoPlayers,
+        ..settings
+    };
+    let (a, b) = Loopback::pair(latency, jitter, settings.seed);
+    let mut peers = [
+        Session::new(settings, Player::One, a),
+        Session::new(settings, Player::Two, b),
+    ];
+    let mut random = Random::new(settings.seed);
+    let mut movements = [0.0; 2];
+    let mut holds = [0; 2];
+    let mut time = Duration::ZERO;
+    while !peers.iter().all(|peer| peer.confirmed().is_over()) && time < limit {
+        for (i, peer) in peers.iter_mut().enumerate() {
+            if holds[i] == 0 {
+                movements[i] = random.range(-1.0, 1.0);
+                holds[i] = 12 + random.next_u64() % 120;
+            }
+            // Transports on the same process never fail.
+            if let Ok(true) = peer.advance(movements[i]) {
+                holds[i] -= 1;
+            }
+        }
+        time += STEP;
+    }
+    let [a, b] = &peers;
+    let court = a.confirmed();
+    let (l_points, r_points) = court.points();
+    let (l_sets, r_sets) = court.sets();
+    RollbackResult {
+        result: MatchResult {
+            l_points,
+            r_points,
+            l_sets,
+            r_sets,
+            duration: court.elapsed(),
+            finished: court.is_over(),
+        },
+        rollbacks: (a.rollbacks(), b.rollbacks()),
+        checksums: (
+            court.snapshot().checksum(),
+            b.confirmed().snapshot().checksum(),
+        ),
+        converged: court.snapshot() == b.confirmed().snapshot()
+            && a.recording().inputs == b.recording().inputs,
+    }
+}
@@ -0,0 +1,268 @@
+/// A platform-neutral keyboard key recognized by the application.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Escape,
+    Space,
+    Tab,
+    Backspace,
+    Shift,
+    Control,
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    Num0,
+    Num1,
+    Num2,
+    Num3,
+    Num4,
+    Num5,
+    Num6,
+    Num7,
+    Num8,
+    Num9,
+    Period,
+    F12,
+}
+
+impl Key {
+    /// All recognized keys.
+    pub const ALL: [Key; 49] = [
+        Key::Up,
+        Key::Down,
+        Key::Left,
+        Key::Right,
+        Key::Enter,
+        Key::Escape,
+        Key::Space,
+        Key::Tab,
+        Key::Backspace,
+        Key::Shift,
+        Key::Control,
+        Key::A,
+        Key::B,
+        Key::C,
+        Key::D,
+        Key::E,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::I,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::M,
+        Key::N,
+        Key::O,
+        Key::P,
+        Key::Q,
+        Key::R,
+        Key::S,
+        Key::T,
+        Key::U,
+        Key::V,
+        Key::W,
+        Key::X,
+        Key::Y,
+        Key::Z,
+        Key::Num0,
+        Key::Num1,
+        Key::Num2,
+        Key::Num3,
+        Key::Num4,
+        Key::Num5,
+        Key::Num6,
+        Key::Num7,
+        Key::Num8,
+        Key::Num9,
+        Key::Period,
+        Key::F12,
+    ];
+
+    /// Get the name of the key shown to the players.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Key::Up => "UP",
+            Key::Down => "DOWN",
+            Key::Left => "LEFT",
+            Key::Right => "RIGHT",
+            Key::Enter => "ENTER",
+            Key::Escape => "ESCAPE",
+            Key::Space => "SPACE",
+            Key::Tab => "TAB",
+            Key::Backspace => "BACKSPACE",
+            Key::Shift => "SHIFT",
+            Key::Control => "CONTROL",
+            Key::A => "A",
+            Key::B => "B",
+            Key::C => "C",
+            Key::D => "D",
+            Key::E => "E",
+            Key::F => "F",
+            Key::G => "G",
+            Key::H => "H",
+            Key::I => "I",
+            Key::J => "J",
+            Key::K => "K",
+            Key::L => "L",
+            Key::M => "M",
+            Key::N => "N",
+            Key::O => "O",
+            Key::P => "P",
+            Key::Q => "Q",
+            Key::R => "R",
+            Key::S => "S",
+            Key::T => "T",
+            Key::U => "U",
+            Key::V => "V",
+            Key::W => "W",
+            Key::X => "X",
+            Key::Y => "Y",
+            Key::Z => "Z",
+            Key::Num0 => "0",
+            Key::Num1 => "1",
+            Key::Num2 => "2",
+            Key::Num3 => "3",
+            Key::Num4 => "4",
+            Key::Num5 => "5",
+            Key::Num6 => "6",
+            Key::Num7 => "7",
+            Key::Num8 => "8",
+            Key::Num9 => "9",
+            Key::Period => "PERIOD",
+            Key::F12 => "F12",
+        }
+    }
+
+    /// Find the key with the given name ignoring the case.
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|key| key.label().eq_ignore_ascii_case(label))
+    }
+}
+
+/// A platform-neutral input event which is routed to the active scene.
+///
+/// The positions of the mouse events are in the pixels of the output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputEvent {
+    KeyDown(Key),
+    KeyUp(Key),
+    MouseMove { x: i32, y: i32 },
+    MouseClick { x: i32, y: i32 },
+}
+
+/// One of the human players of a match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Player {
+    One,
+    Two,
+}
+
+/// A command of the game which is triggered with the key bound to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    MoveUp(Player),
+    MoveDown(Player),
+    Pause,
+    MenuUp,
+    MenuDown,
+    MenuLeft,
+    MenuRight,
+    Confirm,
+    Back,
+}
+
+impl Action {
+    /// All actions in the order they are shown to the players.
+    pub const ALL: [Action; 11] = [
+        Action::MoveUp(Player::One),
+        Action::MoveDown(Player::One),
+        Action::MoveUp(Player::Two),
+        Action::MoveDown(Player::Two),
+        Action::Pause,
+        Action::MenuUp,
+        Action::MenuDown,
+        Action::MenuLeft,
+        Action::MenuRight,
+        Action::Confirm,
+        Action::Back,
+    ];
+
+    /// Get the name of the action shown to the players.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::MoveUp(Player::One) => "P1 UP",
+            Action::MoveDown(Player::One) => "P1 DOWN",
+            Action::MoveUp(Player::Two) => "P2 UP",
+            Action::MoveDown(Player::Two) => "P2 DOWN",
+            Action::Pause => "PAUSE",
+            Action::MenuUp => "MENU UP",
+            Action::MenuDown => "MENU DOWN",
+            Action::MenuLeft => "MENU LEFT",
+            Action::MenuRight => "MENU RIGHT",
+            Action::Confirm => "CONFIRM",
+            Action::Back => "BACK",
+        }
+    }
+
+    /// Get the name of the action in the options file e.g. `p1_up`.
+    pub fn name(&self) -> String {
+        self.label().to_ascii_lowercase().replace(' ', "_")
+    }
+
+    /// Find the action with the given name in the options file.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    /// Check whether the action is used in the menus instead of during a match.
+    ///
+    /// A key may be bound to both a menu action and a match action, but not to two actions of the
+    /// same kind.
+    pub fn is_menu(&self) -> bool {
+        !matches!(
+            self,
+            Action::MoveUp(_) | Action::MoveDown(_) | Action::Pause
+        )
+    }
+}
+
+/// A change in the controls of the paddles which is applied by the court.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ActionEvent {
+    Pressed(Action),
+    Released(Action),
+    /// The paddle of the player follows a pointer at the given vertical position of the court.
+    Aimed(Player, f32),
+    /// The paddle of the player moves with the given speed from an analog axis in range [-1, 1].
+    Steered(Player, f32),
+}
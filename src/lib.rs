@@ -0,0 +1,31 @@
+pub mod ai;
+pub mod app;
+pub mod audio;
+pub mod bindings;
+pub mod codec;
+pub mod config;
+pub mod context;
+pub mod controller;
+pub mod events;
+#[cfg(windows)]
+pub mod gamepad;
+pub mod geometry;
+#[cfg(windows)]
+pub mod graphics;
+pub mod headless;
+pub mod input;
+pub mod network;
+pub mod options;
+pub mod random;
+pub mod recording;
+pub mod renderer;
+pub mod rollback;
+pub mod rules;
+pub mod scenes;
+pub mod screenshot;
+pub mod software;
+#[cfg(windows)]
+pub mod speaker;
+pub mod statistics;
+pub mod timer;
+pub mod tuning;
@@ -1,99 +1,225 @@
-use app::App;
-use graphics::Graphics;
-use windows::core::Result;
-use windows::s;
-use windows::Win32::Foundation::*;
-use windows::Win32::System::LibraryLoader::GetModuleHandleA;
-use windows::Win32::UI::WindowsAndMessaging::*;
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Duration;
 
-mod app;
-mod geometry;
-mod graphics;
-mod scenes;
-mod timer;
+use rust_pong::ai::Difficulty;
+use rust_pong::audio::WavSink;
+use rust_pong::headless::{run_rollback, Simulation};
+use rust_pong::options::{Options, OPTIONS_FILE};
+use rust_pong::recording::Recording;
+use rust_pong::rules::MatchRules;
+use rust_pong::scenes::{MatchSettings, Mode};
+use rust_pong::tuning::{Tuning, TUNING_FILE};
 
-fn main() -> Result<()> {
-    let window = create_window();
-    let gfx = Graphics::new(window)?;
-    let mut app = App::new(gfx);
-    let mut msg = MSG::default();
-    unsafe { SetWindowLongPtrA(window, GWLP_USERDATA, &mut app as *mut _ as _) };
-    while app.running() {
-        unsafe {
-            // Check and acquire system messages from the message queue.
-            while PeekMessageA(&mut msg, HWND(0), 0, 0, PM_REMOVE).into() {
-                if msg.message == WM_QUIT {
-                    return Ok(());
-                }
-                TranslateMessage(&msg);
-                DispatchMessageA(&msg);
-            }
+#[cfg(windows)]
+mod window;
+
+/// The maximum amount of simulated time for a headless match.
+const HEADLESS_TIME_LIMIT: Duration = Duration::from_secs(60 * 60);
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let tuning = load_tuning();
+    let recording = option(&args, "--replay").map(|path| {
+        Recording::load(Path::new(path))
+            .unwrap_or_else(|error| fail(format!("Failed to load replay {}: {}", path, error)))
+    });
+    if args.iter().any(|arg| arg == "--headless") && option(&args, "--rollback").is_some() {
+        run_headless_rollback(&args, &tuning);
+    } else if args.iter().any(|arg| arg == "--headless") {
+        let mut simulation = match recording {
+            Some(recording) => Simulation::from_recording(&recording),
+            None => Simulation::new(headless_settings(&args, &tuning), Vec::new()),
+        };
+        // The final frame looks like the frames of the windowed game.
+        let palette = Options::load_or_default(Path::new(OPTIONS_FILE))
+            .theme
+            .palette();
+        simulation = simulation.with_view(palette, tuning.aspect);
+        let audio = option(&args, "--audio")
+            .map(|path| (Path::new(path), Rc::new(RefCell::new(WavSink::default()))));
+        if let Some((_, sink)) = &audio {
+            simulation = simulation.with_audio(sink.clone());
         }
-        app.tick();
-        app.draw();
+        run_headless(
+            simulation,
+            option(&args, "--screenshot").map(Path::new),
+            option(&args, "--record").map(Path::new),
+            audio,
+        );
+    } else {
+        run_windowed(recording, tuning);
     }
-    Ok(())
 }
 
-fn create_window() -> HWND {
-    unsafe {
-        // Acquire the module handle of the application.
-        let instance = GetModuleHandleA(None).unwrap();
-        debug_assert!(!instance.is_invalid());
+/// Get the value which follows the given option name in the command line arguments.
+fn option<'a>(args: &'a [String], name: &str) -> Option<&'a String> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1))
+}
 
-        // Register a window class for the application.
-        let class_name = s!("window");
-        let class_result = RegisterClassA(&WNDCLASSA {
-            hCursor: LoadCursorW(None, IDC_ARROW).unwrap(),
-            hInstance: instance,
-            lpszClassName: class_name,
-            style: CS_HREDRAW | CS_VREDRAW,
-            lpfnWndProc: Some(wndproc),
-            ..Default::default()
-        });
-        debug_assert!(class_result != 0);
+/// Print the given error message and exit the application with a failure code.
+fn fail(message: impl Display) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
 
-        // Build the application window.
-        CreateWindowExA(
-            WINDOW_EX_STYLE::default(),
-            class_name,
-            s!("Pong"),
-            WS_OVERLAPPEDWINDOW | WS_VISIBLE,
-            CW_USEDEFAULT,
-            CW_USEDEFAULT,
-            CW_USEDEFAULT,
-            CW_USEDEFAULT,
-            None,
-            None,
-            instance,
-            None,
-        )
+/// Load the tunables and exit the application if the tuning file contains errors.
+fn load_tuning() -> Tuning {
+    let path = Path::new(TUNING_FILE);
+    match Tuning::load(path) {
+        Ok(tuning) => tuning,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Tuning::default(),
+        Err(error) => fail(format!(
+            "Failed to load tuning {}: {}",
+            path.display(),
+            error
+        )),
     }
 }
 
-/// A message router for the incoming operating system messages for the application.
-unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
-    let app = GetWindowLongPtrA(hwnd, GWLP_USERDATA) as *mut App;
-    if !app.is_null() {
-        match msg {
-            WM_DESTROY => {
-                PostQuitMessage(0);
-                return LRESULT(0);
-            }
-            WM_SIZE => {
-                (*app).resize();
-                return LRESULT(0);
-            }
-            WM_KEYDOWN => {
-                (*app).key_down(wparam.0 as u16);
-                return LRESULT(0);
-            }
-            WM_KEYUP => {
-                (*app).key_up(wparam.0 as u16);
-                return LRESULT(0);
-            }
-            _ => (),
+/// Build the settings for a headless match between the computer and the reference bot from the command line arguments.
+fn headless_settings(args: &[String], tuning: &Tuning) -> MatchSettings {
+    let seed = match option(args, "--seed").map(|seed| seed.parse()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(_)) => fail("The --seed option requires a non-negative integer value."),
+        None => 0,
+    };
+    let difficulty = match option(args, "--difficulty").map(|d| Difficulty::from_label(d)) {
+        Some(Some(difficulty)) => difficulty,
+        Some(None) => {
+            fail("The --difficulty option requires one of: easy, normal, hard or impossible.")
+        }
+        None => Difficulty::Normal,
+    };
+    let rules = match option(args, "--rules").map(|r| MatchRules::from_name(r)) {
+        Some(Some(rules)) => rules,
+        Some(None) => {
+            fail("The --rules option requires one of: classic, short, deuce, timed or sets.")
+        }
+        None => MatchRules::CLASSIC,
+    };
+    MatchSettings {
+        mode: Mode::Demo,
+        difficulty,
+        rules,
+        physics: tuning.physics,
+        seed,
+    }
+}
+
+/// Run the given simulation until the match is over and print the results.
+///
+/// The final frame, the recording and the rendered sounds of the match are saved into the given
+/// files.
+fn run_headless(
+    mut simulation: Simulation,
+    screenshot: Option<&Path>,
+    record: Option<&Path>,
+    audio: Option<(&Path, Rc<RefCell<WavSink>>)>,
+) {
+    let result = simulation.run(HEADLESS_TIME_LIMIT);
+    let sets = if simulation.court().settings().rules.sets > 1 {
+        format!(" (sets {} - {})", result.l_sets, result.r_sets)
+    } else {
+        String::new()
+    };
+    let status = if result.finished {
+        ""
+    } else {
+        " (time limit reached)"
+    };
+    println!(
+        "left {} - right {}{} after {:.1} seconds{}",
+        result.l_points,
+        result.r_points,
+        sets,
+        result.duration.as_secs_f32(),
+        status
+    );
+    if let Some(path) = screenshot {
+        if let Err(error) = simulation.screenshot(path) {
+            fail(format!(
+                "Failed to save screenshot {}: {}",
+                path.display(),
+                error
+            ));
+        }
+    }
+    if let Some(path) = record {
+        if let Err(error) = simulation.court().recording().save(path) {
+            fail(format!(
+                "Failed to save replay {}: {}",
+                path.display(),
+                error
+            ));
         }
     }
-    DefWindowProcA(hwnd, msg, wparam, lparam)
+    if let Some((path, audio)) = audio {
+        if let Err(error) = audio.borrow().save(path) {
+            fail(format!(
+                "Failed to save audio {}: {}",
+                path.display(),
+                error
+            ));
+        }
+    }
+}
+
+/// Run a headless match between two rollback sessions with the latency and the jitter from the
+/// command line arguments and check that both peers end up in the same state.
+fn run_headless_rollback(args: &[String], tuning: &Tuning) {
+    let milliseconds = |name: &str| match option(args, name).map(|value| value.parse()) {
+        Some(Ok(value)) => Duration::from_millis(value),
+        Some(Err(_)) => fail(format!(
+            "The {} option requires a non-negative amount of milliseconds.",
+            name
+        )),
+        None => Duration::ZERO,
+    };
+    let latency = milliseconds("--rollback");
+    let jitter = milliseconds("--jitter");
+    let settings = headless_settings(args, tuning);
+    let rollback = run_rollback(settings, latency, jitter, HEADLESS_TIME_LIMIT);
+    let result = rollback.result;
+    let status = if result.finished {
+        ""
+    } else {
+        " (time limit reached)"
+    };
+    println!(
+        "left {} - right {} after {:.1} seconds{} with {} and {} rollbacks",
+        result.l_points,
+        result.r_points,
+        result.duration.as_secs_f32(),
+        status,
+        rollback.rollbacks.0,
+        rollback.rollbacks.1
+    );
+    if !rollback.converged {
+        fail("The peers ended up in different states.");
+    }
+    println!("Both peers ended up in the same state.");
+}
+
+#[cfg(windows)]
+fn run_windowed(recording: Option<Recording>, tuning: Tuning) {
+    use rust_pong::scenes::{MainMenu, Replay, Scene};
+
+    let scene: Box<dyn Scene> = match recording {
+        Some(recording) => Box::new(Replay::new(recording)),
+        None => Box::new(MainMenu::new()),
+    };
+    if let Err(error) = window::run(scene, tuning) {
+        fail(format!("Failed to run the game: {}", error));
+    }
+}
+
+#[cfg(not(windows))]
+fn run_windowed(_recording: Option<Recording>, _tuning: Tuning) {
+    fail("The windowed game is only supported on Windows. Use --headless instead.");
 }
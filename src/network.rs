@@ -0,0 +1,471 @@
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::{
+    codec::{self, invalid, read_bytes, read_f32},
+    recording::{read_settings, write_settings},
+    scenes::{MatchSettings, Snapshot},
+};
+
+/// The bytes which identify a packet of the game.
+const MAGIC: &[u8; 4] = b"PONG";
+
+/// The version of the network protocol which must be the same on both ends.
+///
+/// The settings are sent in the current recording format, so changes to it require a new version.
+pub const PROTOCOL_VERSION: u8 = 2;
+
+/// The UDP port where the host waits for a player to join.
+pub const DEFAULT_PORT: u16 = 7777;
+
+/// The UDP port where the host of a network match waits for spectators.
+pub const SPECTATOR_PORT: u16 = DEFAULT_PORT + 1;
+
+/// The maximum amount of spectators which may watch a network match at the same time.
+pub const MAX_SPECTATORS: usize = 8;
+
+/// The time without any packets from the other end after which the connection is considered lost.
+pub const TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The size of the largest packet which is received.
+const MAX_PACKET_SIZE: usize = 512;
+
+/// The way the two ends of a network match keep their courts in sync.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Netcode {
+    /// The host simulates the match and sends its state to the client.
+    #[default]
+    Authoritative,
+    /// Both ends simulate the match and roll back when the inputs of the other end arrive late.
+    Rollback,
+}
+
+impl Netcode {
+    /// All netcodes in the order they are selected.
+    pub const ALL: [Netcode; 2] = [Netcode::Authoritative, Netcode::Rollback];
+
+    /// Get the name of the netcode shown to the players.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Netcode::Authoritative => "HOST",
+            Netcode::Rollback => "ROLLBACK",
+        }
+    }
+
+    /// Find the netcode with the given name ignoring the case.
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|netcode| netcode.label().eq_ignore_ascii_case(label))
+    }
+
+    /// Get the other netcode.
+    pub fn next(&self) -> Self {
+        match self {
+            Netcode::Authoritative => Netcode::Rollback,
+            Netcode::Rollback => Netcode::Authoritative,
+        }
+    }
+}
+
+/// A message which is sent between the host and the client of a network match.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Message {
+    /// A request of a client to join the match of the host.
+    Hello,
+    /// An acceptance of the host which contains the settings of the match and its netcode.
+    Welcome(MatchSettings, Netcode),
+    /// The movement of the paddle of the client in range [-1, 1].
+    Input(f32),
+    /// The authoritative state of the match simulated by the host.
+    State(Snapshot),
+    /// A notification that the other end left the match.
+    Bye,
+}
+
+/// Encode the given message into a packet with the given sequence number.
+///
+/// A packet starts with the magic bytes, the protocol version, the sequence number and the kind of
+/// the message which is followed by the contents of the message.
+pub fn encode(sequence: u32, message: &Message) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(64);
+    packet.extend_from_slice(MAGIC);
+    packet.push(PROTOCOL_VERSION);
+    packet.extend_from_slice(&sequence.to_le_bytes());
+    match message {
+        Message::Hello => packet.push(0),
+        Message::Welcome(settings, netcode) => {
+            packet.push(1);
+            codec::append(&mut packet, |packet| write_settings(packet, settings));
+            packet.push(*netcode as u8);
+        }
+        Message::Input(movement) => {
+            packet.push(2);
+            packet.extend_from_slice(&movement.to_le_bytes());
+        }
+        Message::State(snapshot) => {
+            packet.push(3);
+            packet.extend_from_slice(&snapshot.steps.to_le_bytes());
+            packet.extend_from_slice(&(snapshot.elapsed.as_nanos() as u64).to_le_bytes());
+            packet.extend_from_slice(&(snapshot.countdown.as_nanos() as u64).to_le_bytes());
+            let values = [
+                snapshot.ball_x,
+                snapshot.ball_y,
+                snapshot.ball_x_movement,
+                snapshot.ball_y_movement,
+                snapshot.l_paddle_y,
+                snapshot.r_paddle_y,
+            ];
+            for value in values {
+                packet.extend_from_slice(&value.to_le_bytes());
+            }
+            packet.extend_from_slice(&[
+                snapshot.l_points,
+                snapshot.r_points,
+                snapshot.l_sets,
+                snapshot.r_sets,
+            ]);
+        }
+        Message::Bye => packet.push(4),
+    }
+    packet
+}
+
+/// Decode the sequence number and the message from the given packet.
+pub fn decode(packet: &[u8]) -> io::Result<(u32, Message)> {
+    let reader = &mut &packet[..];
+    if &read_bytes::<4>(reader)? != MAGIC {
+        return Err(invalid("not a packet of the game"));
+    }
+    if read_bytes::<1>(reader)? != [PROTOCOL_VERSION] {
+        return Err(invalid("unsupported protocol version"));
+    }
+    let sequence = u32::from_le_bytes(read_bytes(reader)?);
+    let [kind] = read_bytes(reader)?;
+    let message = match kind {
+        0 => Message::Hello,
+        1 => {
+            let settings = read_settings(reader)?;
+            let netcode = match read_bytes(reader)? {
+                [0] => Netcode::Authoritative,
+                [1] => Netcode::Rollback,
+                _ => return Err(invalid("unknown netcode")),
+            };
+            Message::Welcome(settings, netcode)
+        }
+        2 => {
+            let movement = read_f32(reader)?;
+            if !(-1.0..=1.0).contains(&movement) {
+                return Err(invalid("invalid movement"));
+            }
+            Message::Input(movement)
+        }
+        3 => {
+            let steps = u32::from_le_bytes(read_bytes(reader)?);
+            let elapsed = Duration::from_nanos(u64::from_le_bytes(read_bytes(reader)?));
+            let countdown = Duration::from_nanos(u64::from_le_bytes(read_bytes(reader)?));
+            let ball_x = read_f32(reader)?;
+            let ball_y = read_f32(reader)?;
+            let ball_x_movement = read_f32(reader)?;
+            let ball_y_movement = read_f32(reader)?;
+            let l_paddle_y = read_f32(reader)?;
+            let r_paddle_y = read_f32(reader)?;
+            let [l_points, r_points, l_sets, r_sets] = read_bytes(reader)?;
+            Message::State(Snapshot {
+                steps,
+                elapsed,
+                countdown,
+                ball_x,
+                ball_y,
+                ball_x_movement,
+                ball_y_movement,
+                l_paddle_y,
+                r_paddle_y,
+                l_points,
+                r_points,
+                l_sets,
+                r_sets,
+            })
+        }
+        4 => Message::Bye,
+        _ => return Err(invalid("unknown message")),
+    };
+    if !reader.is_empty() {
+        return Err(invalid("unexpected data after the message"));
+    }
+    Ok((sequence, message))
+}
+
+/// A connection to the other end of a network match over UDP.
+///
+/// Packets which arrive after a newer packet are dropped, so the messages must describe the whole
+/// state instead of changes to it in order to tolerate packet loss.
+pub struct Connection {
+    socket: UdpSocket,
+    peer: SocketAddr,
+    /// The sequence number of the next sent packet.
+    sequence: u32,
+    /// The sequence number of the newest received packet.
+    received: Option<u32>,
+    last_received: Instant,
+}
+
+impl Connection {
+    /// Build a new connection to the given peer through the given non-blocking socket.
+    fn new(socket: UdpSocket, peer: SocketAddr) -> Self {
+        Self {
+            socket,
+            peer,
+            sequence: 0,
+            received: None,
+            last_received: Instant::now(),
+        }
+    }
+
+    /// Open a connection from a new local port to the host at the given address.
+    ///
+    /// Nothing is sent yet, so the client must send a hello until the host accepts it.
+    pub fn connect(address: SocketAddr) -> io::Result<Self> {
+        let local = match address {
+            SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            SocketAddr::V6(_) => SocketAddr::from(([0; 16], 0)),
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self::new(socket, address))
+    }
+
+    /// Get the address of the other end.
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// Send the given message to the other end.
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        let packet = encode(self.sequence, message);
+        self.sequence = self.sequence.wrapping_add(1);
+        self.socket.send_to(&packet, self.peer).map(|_| ())
+    }
+
+    /// Send the given packet of another protocol to the other end.
+    pub fn send_packet(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.socket.send_to(packet, self.peer).map(|_| ())
+    }
+
+    /// Receive the messages which have arrived from the other end since the previous call.
+    ///
+    /// Invalid packets, packets from other addresses and outdated packets are silently dropped.
+    pub fn receive(&mut self) -> io::Result<Vec<Message>> {
+        Ok(self.receive_all()?.0)
+    }
+
+    /// Receive the messages and the packets of other protocols which have arrived from the other
+    /// end since the previous call.
+    ///
+    /// The packets of other protocols are passed on as they are, e.g. to a rollback session.
+    pub fn receive_all(&mut self) -> io::Result<(Vec<Message>, Vec<Vec<u8>>)> {
+        let mut messages = Vec::new();
+        let mut packets = Vec::new();
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        loop {
+            let (size, address) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                // An earlier packet could not be delivered, which is detected with the timeout.
+                Err(error) if error.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(error) => return Err(error),
+            };
+            if address != self.peer {
+                continue;
+            }
+            if !buffer[..size].starts_with(MAGIC) {
+                self.last_received = Instant::now();
+                packets.push(buffer[..size].to_vec());
+                continue;
+            }
+            let (sequence, message) = match decode(&buffer[..size]) {
+                Ok(packet) => packet,
+                Err(_) => continue,
+            };
+            if self.received.is_some_and(|received| sequence <= received) {
+                continue;
+            }
+            self.received = Some(sequence);
+            self.last_received = Instant::now();
+            messages.push(message);
+        }
+        Ok((messages, packets))
+    }
+
+    /// Check whether nothing has been received from the other end for too long.
+    pub fn is_lost(&self) -> bool {
+        self.last_received.elapsed() > TIMEOUT
+    }
+}
+
+/// A socket where the host waits for a client to join.
+pub struct Listener {
+    socket: UdpSocket,
+}
+
+impl Listener {
+    /// Start waiting for a client on the given port of all local addresses.
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        socket.set_nonblocking(true)?;
+        Ok(Self { socket })
+    }
+
+    /// Get the local port where the host waits for a client.
+    pub fn port(&self) -> io::Result<u16> {
+        Ok(self.socket.local_addr()?.port())
+    }
+
+    /// Accept the first client which has asked to join and send it the given settings of the match
+    /// and the netcode.
+    pub fn accept(
+        &mut self,
+        settings: &MatchSettings,
+        netcode: Netcode,
+    ) -> io::Result<Option<Connection>> {
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        loop {
+            let (size, address) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+                Err(error) if error.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(error) => return Err(error),
+            };
+            if let Ok((sequence, Message::Hello)) = decode(&buffer[..size]) {
+                let mut connection = Connection::new(self.socket.try_clone()?, address);
+                connection.received = Some(sequence);
+                connection.send(&Message::Welcome(*settings, netcode))?;
+                return Ok(Some(connection));
+            }
+        }
+    }
+}
+
+/// A spectator which watches the match of the host.
+struct Watcher {
+    address: SocketAddr,
+    /// The sequence number of the next sent packet.
+    sequence: u32,
+    last_received: Instant,
+}
+
+/// A socket where the host sends the state of the match to any amount of spectators.
+///
+/// Spectators join by saying hello and keep saying it to show that they are still watching. They
+/// are always sent the states of the host, so their welcome names the authoritative netcode.
+pub struct Spectators {
+    socket: UdpSocket,
+    settings: MatchSettings,
+    watchers: Vec<Watcher>,
+}
+
+impl Spectators {
+    /// Start waiting for spectators of a match with the given settings on the given port.
+    pub fn bind(port: u16, settings: MatchSettings) -> io::Result<Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            settings,
+            watchers: Vec::new(),
+        })
+    }
+
+    /// Get the local port where the host waits for spectators.
+    pub fn port(&self) -> io::Result<u16> {
+        Ok(self.socket.local_addr()?.port())
+    }
+
+    /// Get the amount of spectators which are watching the match.
+    pub fn count(&self) -> usize {
+        self.watchers.len()
+    }
+
+    /// Accept the new spectators and forget the spectators which have left or gone silent.
+    ///
+    /// Each hello is answered with the settings of the match, which also lets a spectator join the
+    /// match when the first answer was lost.
+    pub fn update(&mut self) -> io::Result<()> {
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        loop {
+            let (size, address) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(error) => return Err(error),
+            };
+            let index = self.watchers.iter().position(|w| w.address == address);
+            match (decode(&buffer[..size]), index) {
+                (Ok((_, Message::Hello)), Some(index)) => {
+                    self.watchers[index].last_received = Instant::now();
+                    self.send(
+                        index,
+                        &Message::Welcome(self.settings, Netcode::Authoritative),
+                    );
+                }
+                (Ok((_, Message::Hello)), None) if self.watchers.len() < MAX_SPECTATORS => {
+                    self.watchers.push(Watcher {
+                        address,
+                        sequence: 0,
+                        last_received: Instant::now(),
+                    });
+                    self.send(
+                        self.watchers.len() - 1,
+                        &Message::Welcome(self.settings, Netcode::Authoritative),
+                    );
+                }
+                (Ok((_, Message::Bye)), Some(index)) => {
+                    self.watchers.remove(index);
+                }
+                _ => (),
+            }
+        }
+        self.watchers
+            .retain(|watcher| watcher.last_received.elapsed() <= TIMEOUT);
+        Ok(())
+    }
+
+    /// Send the given message to all spectators.
+    pub fn broadcast(&mut self, message: &Message) {
+        for index in 0..self.watchers.len() {
+            self.send(index, message);
+        }
+    }
+
+    /// Send the given message to the spectator with the given index.
+    ///
+    /// Spectators which cannot be reached are dropped by the timeout, so errors are ignored.
+    fn send(&mut self, index: usize, message: &Message) {
+        let watcher = &mut self.watchers[index];
+        let packet = encode(watcher.sequence, message);
+        watcher.sequence = watcher.sequence.wrapping_add(1);
+        let _ = self.socket.send_to(&packet, watcher.address);
+    }
+}
+
+impl Drop for Spectators {
+    /// Tell the spectators that the match has been stopped.
+    fn drop(&mut self) {
+        self.broadcast(&Message::Bye);
+    }
+}
+
+/// Find the local address which other machines of the network most likely use to reach this one.
+///
+/// The address is resolved by the routing of the operating system without sending any packets.
+pub fn local_address() -> Option<Ipv4Addr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket
+        .connect((Ipv4Addr::new(192, 168, 0, 1), DEFAULT_PORT))
+        .ok()?;
+    match socket.local_addr().ok()? {
+        SocketAddr::V4(address) if !address.ip().is_unspecified() => Some(*address.ip()),
+        _ => None,
+    }
+}
@@ -0,0 +1,353 @@
+use std::fs;
+use std::io;
+use std::net::Ipv4Addr;
+use std::path::Path;
+
+use crate::{
+    ai::Difficulty,
+    bindings::{Bindings, RESERVED_KEY},
+    config::{self, Value},
+    input::{Action, Key},
+    network::Netcode,
+    renderer::{Color, Palette},
+    rules::MatchRules,
+    scenes::{MatchSettings, Mode, Physics},
+};
+
+/// The file where the options are saved between the runs.
+pub const OPTIONS_FILE: &str = "options.toml";
+
+/// The smallest percentage of the default value which can be selected for a scaled option.
+const MIN_PERCENT: u8 = 50;
+
+/// The largest percentage of the default value which can be selected for a scaled option.
+const MAX_PERCENT: u8 = 150;
+
+/// The change of a scaled option with a single step.
+const PERCENT_STEP: u8 = 25;
+
+/// The selectable color themes of the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Theme {
+    Classic,
+    Green,
+    Amber,
+}
+
+impl Theme {
+    /// All themes in the order they are selected.
+    pub const ALL: [Theme; 3] = [Theme::Classic, Theme::Green, Theme::Amber];
+
+    /// Get the name of the theme shown to the players.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::Classic => "CLASSIC",
+            Theme::Green => "GREEN",
+            Theme::Amber => "AMBER",
+        }
+    }
+
+    /// Find the theme with the given name ignoring the case.
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|theme| theme.label().eq_ignore_ascii_case(label))
+    }
+
+    /// Get the next theme or the first theme after the last one.
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|t| t == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Get the previous theme or the last theme before the first one.
+    pub fn previous(&self) -> Self {
+        let index = Self::ALL.iter().position(|t| t == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Get the colors used to draw the game with the theme.
+    pub fn palette(&self) -> Palette {
+        match self {
+            Theme::Classic => Palette::default(),
+            Theme::Green => Palette {
+                foreground: Color::rgb(0.2, 1.0, 0.4),
+                background: Color::rgb(0.0, 0.08, 0.02),
+            },
+            Theme::Amber => Palette {
+                foreground: Color::rgb(1.0, 0.7, 0.1),
+                background: Color::rgb(0.1, 0.05, 0.0),
+            },
+        }
+    }
+}
+
+/// The options selected by the players which are saved between the runs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Options {
+    pub rules: MatchRules,
+    /// The initial speed of the ball as a percentage of the default speed.
+    pub ball_speed: u8,
+    /// The maximum speed of the ball as a percentage of the default maximum speed.
+    pub max_speed: u8,
+    /// The height of the paddles as a percentage of the default height.
+    pub paddle_size: u8,
+    pub difficulty: Difficulty,
+    pub theme: Theme,
+    /// The netcode of the network matches which are hosted.
+    pub netcode: Netcode,
+    pub bindings: Bindings,
+    /// The address of the host which was joined the last time.
+    pub join_address: Ipv4Addr,
+}
+
+impl Options {
+    /// Load the options from the given file or use the defaults if the file cannot be loaded.
+    ///
+    /// A missing file is silently ignored while other failures are reported.
+    pub fn load_or_default(path: &Path) -> Self {
+        match Self::load(path) {
+            Ok(options) => options,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(error) => {
+                eprintln!("Failed to load options {}: {}", path.display(), error);
+                Self::default()
+            }
+        }
+    }
+
+    /// Load the options from the given file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let source = fs::read_to_string(path)?;
+        Self::parse(&source).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Save the options into the given file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_config())
+    }
+
+    /// Parse the options from the contents of an options file.
+    ///
+    /// The options which are not defined in the file use their default values.
+    pub fn parse(source: &str) -> Result<Self, config::Error> {
+        let mut options = Self::default();
+        // The bound keys with the lines where they are defined.
+        let mut bound = Vec::new();
+        for entry in config::parse(source)? {
+            let percent = || entry.as_integer(MIN_PERCENT.into(), MAX_PERCENT.into());
+            match (entry.section.as_str(), entry.key.as_str()) {
+                ("", "rules") => {
+                    options.rules = MatchRules::from_name(entry.as_str()?)
+                        .ok_or_else(|| entry.error("unknown rules"))?
+                }
+                ("", "ball_speed") => options.ball_speed = percent()? as u8,
+                ("", "max_speed") => options.max_speed = percent()? as u8,
+                ("", "paddle_size") => options.paddle_size = percent()? as u8,
+                ("", "difficulty") => {
+                    options.difficulty = Difficulty::from_label(entry.as_str()?)
+                        .ok_or_else(|| entry.error("unknown difficulty"))?
+                }
+                ("", "theme") => {
+                    options.theme = Theme::from_label(entry.as_str()?)
+                        .ok_or_else(|| entry.error("unknown theme"))?
+                }
+                ("", "netcode") => {
+                    options.netcode = Netcode::from_label(entry.as_str()?)
+                        .ok_or_else(|| entry.error("unknown netcode"))?
+                }
+                ("", "join_address") => {
+                    options.join_address = entry
+                        .as_str()?
+                        .parse()
+                        .map_err(|_| entry.error("expected an IPv4 address"))?
+                }
+                ("bindings", name) => {
+                    let action = Action::from_name(name).ok_or_else(|| config::Error {
+                        line: entry.line,
+                        message: format!("unknown action '{}'", name),
+                    })?;
+                    let key = Key::from_label(entry.as_str()?)
+                        .filter(|key| *key != RESERVED_KEY)
+                        .ok_or_else(|| entry.error("unknown or reserved key"))?;
+                    bound.push((action, key, entry.line));
+                }
+                _ => return Err(entry.unknown("option")),
+            }
+        }
+        // The keys are bound only after all of them are known as they may be swapped in the file.
+        for &(action, key, _) in &bound {
+            options.bindings.set(action, key);
+        }
+        for &(action, key, line) in &bound {
+            if let Some(other) = options.bindings.conflict(action, key) {
+                return Err(config::Error {
+                    line,
+                    message: format!(
+                        "key '{}' is bound to both {} and {}",
+                        key.label().to_ascii_lowercase(),
+                        other.name(),
+                        action.name()
+                    ),
+                });
+            }
+        }
+        Ok(options)
+    }
+
+    /// Write the options in the format of an options file.
+    pub fn to_config(&self) -> String {
+        let entries = [
+            ("rules", text(self.rules.name())),
+            ("ball_speed", Value::Integer(self.ball_speed.into())),
+            ("max_speed", Value::Integer(self.max_speed.into())),
+            ("paddle_size", Value::Integer(self.paddle_size.into())),
+            ("difficulty", text(self.difficulty.label())),
+            ("theme", text(self.theme.label())),
+            ("netcode", text(self.netcode.label())),
+            ("join_address", Value::String(self.join_address.to_string())),
+        ];
+        let mut config: String = entries
+            .iter()
+            .map(|(key, value)| format!("{} = {}\n", key, value))
+            .collect();
+        config.push_str("\n[bindings]\n");
+        for action in Action::ALL {
+            let key = self.bindings.key(action);
+            config.push_str(&format!("{} = {}\n", action.name(), text(key.label())));
+        }
+        config
+    }
+
+    /// Scale the given physical properties of the court with the selected percentages.
+    ///
+    /// The maximum speed of the ball is kept at least as high as its start speed, and the paddles
+    /// are kept short enough to fit between the walls.
+    pub fn physics(&self, base: &Physics) -> Physics {
+        let ball_velocity = base.ball_velocity * scale(self.ball_speed);
+        let paddle_height = base.paddle_height * scale(self.paddle_size);
+        Physics {
+            ball_velocity,
+            ball_max_velocity: (base.ball_max_velocity * scale(self.max_speed)).max(ball_velocity),
+            paddle_height: paddle_height.min(base.max_paddle_height()),
+            ..*base
+        }
+    }
+
+    /// Build the settings for a new match with the given mode, seed and base physics.
+    pub fn match_settings(&self, mode: Mode, seed: u64, physics: &Physics) -> MatchSettings {
+        MatchSettings {
+            mode,
+            difficulty: self.difficulty,
+            rules: self.rules,
+            physics: self.physics(physics),
+            seed,
+        }
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            rules: MatchRules::CLASSIC,
+            ball_speed: 100,
+            max_speed: 100,
+            paddle_size: 100,
+            difficulty: Difficulty::Normal,
+            theme: Theme::Classic,
+            netcode: Netcode::Authoritative,
+            bindings: Bindings::default(),
+            join_address: Ipv4Addr::LOCALHOST,
+        }
+    }
+}
+
+/// Change the given percentage by a single step without leaving the selectable range.
+pub fn step_percent(percent: u8, forward: bool) -> u8 {
+    let percent = if forward {
+        percent.saturating_add(PERCENT_STEP)
+    } else {
+        percent.saturating_sub(PERCENT_STEP)
+    };
+    percent.clamp(MIN_PERCENT, MAX_PERCENT)
+}
+
+/// Convert the given percentage into a scalar.
+fn scale(percent: u8) -> f32 {
+    percent as f32 / 100.0
+}
+
+/// Build a lowercase string value from the given label.
+fn text(label: &str) -> Value {
+    Value::String(label.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{input::Player, scenes::Court, tuning::Tuning};
+    use std::time::Duration;
+
+    #[test]
+    fn max_ball_speed_is_not_below_the_start_speed() {
+        let options = Options {
+            ball_speed: 150,
+            max_speed: 50,
+            ..Options::default()
+        };
+        let physics = options.physics(&Physics::default());
+        assert_eq!(physics.ball_max_velocity, physics.ball_velocity);
+        assert!(physics.ball_velocity > Physics::default().ball_velocity);
+    }
+
+    #[test]
+    fn largest_paddles_fit_between_the_walls() {
+        let tuning = Tuning::parse("[physics]\npaddle_height = 0.9\n").unwrap();
+        let options = Options {
+            paddle_size: MAX_PERCENT,
+            ..Options::default()
+        };
+        let settings = options.match_settings(Mode::TwoPlayers, 0, &tuning.physics);
+        assert_eq!(settings.physics.validate(), Ok(()));
+
+        // The paddles are pushed against both walls after the countdown.
+        let mut court = Court::new(settings);
+        for step in 0..240 {
+            let movement = if step < 120 { -1.0 } else { 1.0 };
+            court.steer(Player::One, movement);
+            court.update(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn rebound_options_round_trip_through_the_file() {
+        let mut options = Options {
+            rules: MatchRules::PRESETS[2].1,
+            paddle_size: 75,
+            theme: Theme::Amber,
+            netcode: Netcode::Rollback,
+            join_address: Ipv4Addr::new(192, 168, 0, 10),
+            ..Options::default()
+        };
+        // The keys of the players are swapped, which is only valid once both are bound.
+        options.bindings.set(Action::MoveUp(Player::One), Key::Up);
+        options.bindings.set(Action::MoveUp(Player::Two), Key::W);
+        options.bindings.set(Action::Confirm, Key::Space);
+        assert_eq!(Options::parse(&options.to_config()), Ok(options));
+    }
+
+    #[test]
+    fn conflicting_keys_are_reported() {
+        let error = Options::parse("[bindings]\np1_up = \"s\"\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: key 's' is bound to both p1_down and p1_up"
+        );
+    }
+
+    #[test]
+    fn unknown_option_is_reported() {
+        let error = Options::parse("ball_speed = 100\nspin = 3\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: unknown option 'spin'");
+    }
+}
@@ -0,0 +1,380 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::{
+    ai::Difficulty,
+    codec::{invalid, read_bytes, read_f32},
+    input::{Action, ActionEvent, Player},
+    rules::MatchRules,
+    scenes::{MatchSettings, Mode, Physics},
+    timer::STEP,
+};
+
+/// The bytes which identify a recording file.
+const MAGIC: &[u8; 4] = b"PRPL";
+
+/// The version of the recording file format.
+pub const VERSION: u8 = 1;
+
+/// The file where the recording of the latest finished match is saved.
+pub const LAST_MATCH: &str = "last.replay";
+
+/// An action event which was received before the simulation step with the given index.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RecordedInput {
+    pub step: u32,
+    pub event: ActionEvent,
+}
+
+/// A recording of a match which contains everything needed to play it back step by step.
+#[derive(Clone, Debug)]
+pub struct Recording {
+    pub settings: MatchSettings,
+    pub inputs: Vec<RecordedInput>,
+}
+
+impl Recording {
+    /// Build a new empty recording of a match with the given settings.
+    pub fn new(settings: MatchSettings) -> Self {
+        Self {
+            settings,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Add the given action event which was received before the simulation step with the given index.
+    pub fn push(&mut self, step: u32, event: ActionEvent) {
+        self.inputs.push(RecordedInput { step, event });
+    }
+
+    /// Remove the action events which were received before the given step or any later step.
+    pub fn truncate(&mut self, step: u32) {
+        let end = self.inputs.partition_point(|input| input.step < step);
+        self.inputs.truncate(end);
+    }
+
+    /// Load a recording from the given file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    /// Save the recording into the given file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Write the recording in the binary recording format.
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&(STEP.as_nanos() as u32).to_le_bytes())?;
+        write_settings(writer, &self.settings)?;
+        writer.write_all(&(self.inputs.len() as u32).to_le_bytes())?;
+        for input in &self.inputs {
+            writer.write_all(&input.step.to_le_bytes())?;
+            match input.event {
+                ActionEvent::Pressed(action) => writer.write_all(&[0, encode_action(action)])?,
+                ActionEvent::Released(action) => writer.write_all(&[1, encode_action(action)])?,
+                ActionEvent::Aimed(player, y) => {
+                    writer.write_all(&[2, encode_player(player)])?;
+                    writer.write_all(&y.to_le_bytes())?;
+                }
+                ActionEvent::Steered(player, value) => {
+                    writer.write_all(&[3, encode_player(player)])?;
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a recording which is in the binary recording format.
+    pub fn read(reader: &mut impl Read) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a recording file"));
+        }
+        let [version] = read_bytes(reader)?;
+        if version != VERSION {
+            return Err(invalid("unsupported recording version"));
+        }
+        if u32::from_le_bytes(read_bytes(reader)?) != STEP.as_nanos() as u32 {
+            return Err(invalid("recording uses a different simulation step"));
+        }
+        let settings = read_settings(reader)?;
+        let count = u32::from_le_bytes(read_bytes(reader)?);
+        let mut recording = Recording::new(settings);
+        for _ in 0..count {
+            let step = u32::from_le_bytes(read_bytes(reader)?);
+            let [kind, code] = read_bytes(reader)?;
+            let action = || decode_action(code).ok_or_else(|| invalid("unknown action"));
+            let event = match kind {
+                0 => ActionEvent::Pressed(action()?),
+                1 => ActionEvent::Released(action()?),
+                2 => {
+                    let player = decode_player(code).ok_or_else(|| invalid("unknown player"))?;
+                    let y = f32::from_le_bytes(read_bytes(reader)?);
+                    if !y.is_finite() {
+                        return Err(invalid("invalid aim"));
+                    }
+                    ActionEvent::Aimed(player, y)
+                }
+                3 => {
+                    let player = decode_player(code).ok_or_else(|| invalid("unknown player"))?;
+                    let value = f32::from_le_bytes(read_bytes(reader)?);
+                    if !(-1.0..=1.0).contains(&value) {
+                        return Err(invalid("invalid axis"));
+                    }
+                    ActionEvent::Steered(player, value)
+                }
+                _ => return Err(invalid("unknown input event")),
+            };
+            recording.push(step, event);
+        }
+        Ok(recording)
+    }
+}
+
+/// Write the given match settings in the binary recording format.
+pub fn write_settings(writer: &mut impl Write, settings: &MatchSettings) -> io::Result<()> {
+    writer.write_all(&[
+        encode_mode(settings.mode),
+        encode_difficulty(settings.difficulty),
+    ])?;
+    writer.write_all(&settings.seed.to_le_bytes())?;
+    write_rules(writer, &settings.rules)?;
+    write_physics(writer, &settings.physics)
+}
+
+/// Read match settings which are in the binary recording format.
+pub fn read_settings(reader: &mut impl Read) -> io::Result<MatchSettings> {
+    let [mode, difficulty] = read_bytes(reader)?;
+    Ok(MatchSettings {
+        mode: decode_mode(mode).ok_or_else(|| invalid("unknown match mode"))?,
+        difficulty: decode_difficulty(difficulty).ok_or_else(|| invalid("unknown difficulty"))?,
+        seed: u64::from_le_bytes(read_bytes(reader)?),
+        rules: read_rules(reader)?,
+        physics: read_physics(reader)?,
+    })
+}
+
+/// Write the given match rules in the binary recording format.
+fn write_rules(writer: &mut impl Write, rules: &MatchRules) -> io::Result<()> {
+    let time_limit = rules.time_limit.map_or(0, |limit| limit.as_millis() as u32);
+    writer.write_all(&[rules.target_score, rules.win_by_two as u8, rules.sets])?;
+    writer.write_all(&time_limit.to_le_bytes())
+}
+
+/// Read match rules which are in the binary recording format.
+fn read_rules(reader: &mut impl Read) -> io::Result<MatchRules> {
+    let [target_score, win_by_two, sets] = read_bytes(reader)?;
+    let time_limit = u32::from_le_bytes(read_bytes(reader)?);
+    if target_score == 0 || sets == 0 || win_by_two > 1 {
+        return Err(invalid("invalid match rules"));
+    }
+    Ok(MatchRules {
+        target_score,
+        win_by_two: win_by_two == 1,
+        time_limit: match time_limit {
+            0 => None,
+            millis => Some(Duration::from_millis(millis as u64)),
+        },
+        sets,
+    })
+}
+
+/// Write the given physical properties in the binary recording format.
+fn write_physics(writer: &mut impl Write, physics: &Physics) -> io::Result<()> {
+    let values = [
+        physics.ball_velocity,
+        physics.ball_max_velocity,
+        physics.paddle_height,
+        physics.paddle_velocity,
+        physics.ball_velocity_scalar,
+        physics.max_bounce_angle,
+        physics.nudge,
+    ];
+    for value in values {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    writer.write_all(&(physics.countdown.as_millis() as u32).to_le_bytes())
+}
+
+/// Read physical properties which are in the binary recording format.
+///
+/// The properties must be valid, as the court cannot be played with e.g. paddles which do not fit
+/// between the walls.
+fn read_physics(reader: &mut impl Read) -> io::Result<Physics> {
+    let physics = Physics {
+        ball_velocity: read_f32(reader)?,
+        ball_max_velocity: read_f32(reader)?,
+        paddle_height: read_f32(reader)?,
+        paddle_velocity: read_f32(reader)?,
+        ball_velocity_scalar: read_f32(reader)?,
+        max_bounce_angle: read_f32(reader)?,
+        nudge: read_f32(reader)?,
+        countdown: Duration::from_millis(u32::from_le_bytes(read_bytes(reader)?).into()),
+    };
+    physics
+        .validate()
+        .map_err(|message| invalid(&format!("invalid physics: {}", message)))?;
+    Ok(physics)
+}
+
+/// Get the stable code of the given action in the recording format.
+fn encode_action(action: Action) -> u8 {
+    match action {
+        Action::MoveUp(Player::One) => 0,
+        Action::MoveDown(Player::One) => 1,
+        Action::MoveUp(Player::Two) => 2,
+        Action::MoveDown(Player::Two) => 3,
+        Action::Pause => 4,
+        Action::MenuUp => 5,
+        Action::MenuDown => 6,
+        Action::MenuLeft => 7,
+        Action::MenuRight => 8,
+        Action::Confirm => 9,
+        Action::Back => 10,
+    }
+}
+
+/// Get the action with the given stable code in the recording format.
+fn decode_action(code: u8) -> Option<Action> {
+    Action::ALL
+        .into_iter()
+        .find(|action| encode_action(*action) == code)
+}
+
+/// Get the stable code of the given player in the recording format.
+fn encode_player(player: Player) -> u8 {
+    match player {
+        Player::One => 0,
+        Player::Two => 1,
+    }
+}
+
+/// Get the player with the given stable code in the recording format.
+fn decode_player(code: u8) -> Option<Player> {
+    match code {
+        0 => Some(Player::One),
+        1 => Some(Player::Two),
+        _ => None,
+    }
+}
+
+/// Get the stable code of the given match mode in the recording format.
+fn encode_mode(mode: Mode) -> u8 {
+    match mode {
+        Mode::Demo => 0,
+        Mode::OnePlayer => 1,
+        Mode::TwoPlayers => 2,
+    }
+}
+
+/// Get the match mode with the given stable code in the recording format.
+fn decode_mode(code: u8) -> Option<Mode> {
+    match code {
+        0 => Some(Mode::Demo),
+        1 => Some(Mode::OnePlayer),
+        2 => Some(Mode::TwoPlayers),
+        _ => None,
+    }
+}
+
+/// Get the stable code of the given difficulty in the recording format.
+fn encode_difficulty(difficulty: Difficulty) -> u8 {
+    match difficulty {
+        Difficulty::Easy => 0,
+        Difficulty::Normal => 1,
+        Difficulty::Hard => 2,
+        Difficulty::Impossible => 3,
+    }
+}
+
+/// Get the difficulty with the given stable code in the recording format.
+fn decode_difficulty(code: u8) -> Option<Difficulty> {
+    match code {
+        0 => Some(Difficulty::Easy),
+        1 => Some(Difficulty::Normal),
+        2 => Some(Difficulty::Hard),
+        3 => Some(Difficulty::Impossible),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_round_trips_through_the_format() {
+        let mut recording = Recording::new(MatchSettings {
+            mode: Mode::TwoPlayers,
+            difficulty: Difficulty::Hard,
+            rules: MatchRules::PRESETS[3].1,
+            seed: 1234,
+            ..MatchSettings::default()
+        });
+        recording.push(3, ActionEvent::Pressed(Action::MoveUp(Player::One)));
+        recording.push(9, ActionEvent::Released(Action::MoveUp(Player::One)));
+        recording.push(12, ActionEvent::Aimed(Player::Two, 0.25));
+        recording.push(15, ActionEvent::Steered(Player::One, -0.5));
+        let mut bytes = Vec::new();
+        recording.write(&mut bytes).unwrap();
+
+        let read = Recording::read(&mut &bytes[..]).unwrap();
+        assert_eq!(read.settings, recording.settings);
+        assert_eq!(read.inputs, recording.inputs);
+    }
+
+    #[test]
+    fn recording_of_another_version_is_rejected() {
+        let mut bytes = Vec::new();
+        Recording::new(MatchSettings::default())
+            .write(&mut bytes)
+            .unwrap();
+        bytes[MAGIC.len()] = VERSION + 1;
+        assert!(Recording::read(&mut &bytes[..]).is_err());
+    }
+
+    #[test]
+    fn recording_with_invalid_physics_is_rejected() {
+        let invalid = [
+            Physics {
+                ball_velocity_scalar: 0.5,
+                ..Physics::default()
+            },
+            Physics {
+                paddle_height: 0.975,
+                ..Physics::default()
+            },
+        ];
+        for physics in invalid {
+            let mut bytes = Vec::new();
+            Recording::new(MatchSettings {
+                physics,
+                ..MatchSettings::default()
+            })
+            .write(&mut bytes)
+            .unwrap();
+            let error = Recording::read(&mut &bytes[..]).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn truncated_recording_keeps_the_earlier_steps() {
+        let mut recording = Recording::new(MatchSettings::default());
+        recording.push(3, ActionEvent::Steered(Player::One, 0.5));
+        recording.push(5, ActionEvent::Steered(Player::One, -0.5));
+        recording.push(5, ActionEvent::Steered(Player::Two, 1.0));
+        recording.truncate(5);
+        assert_eq!(recording.inputs.len(), 1);
+        assert_eq!(recording.inputs[0].step, 3);
+    }
+}
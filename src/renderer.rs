@@ -0,0 +1,160 @@
+use crate::geometry::{Rectangle, Text};
+
+/// The default view aspect ratio.
+pub const ASPECT: f32 = 1.3;
+
+/// A color with red, green, blue and alpha components in range [0, 1].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+    pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+
+    /// Build a new opaque color from the given red, green and blue components.
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Color { r, g, b, a: 1.0 }
+    }
+
+    /// Build a copy of the color with the given opacity.
+    pub fn with_alpha(self, a: f32) -> Self {
+        Color { a, ..self }
+    }
+}
+
+/// The colors used to clear the canvas and to draw rectangles and texts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Palette {
+    pub foreground: Color,
+    pub background: Color,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            foreground: Color::WHITE,
+            background: Color::BLACK,
+        }
+    }
+}
+
+/// A rendering context which scenes use to draw their contents.
+pub trait Renderer {
+    /// Clear the canvas and begin drawing a new frame with the palette colors.
+    fn clear(&mut self);
+
+    /// Use the given palette when clearing the following frames.
+    fn set_palette(&mut self, palette: Palette);
+
+    /// Get the palette which is used to clear the frames.
+    fn palette(&self) -> Palette;
+
+    /// Use the given aspect ratio (width / height) for the view inside the canvas.
+    fn set_aspect(&mut self, aspect: f32);
+
+    /// Get the aspect ratio of the view inside the canvas.
+    fn aspect(&self) -> f32;
+
+    /// Get the width and the height of the canvas in pixels.
+    fn size(&self) -> (u32, u32);
+
+    /// Use the given color to draw the following rectangles and texts.
+    fn set_color(&mut self, color: Color);
+
+    /// Draw the given rectangle on the canvas.
+    fn draw_rectangle(&mut self, rectangle: &Rectangle);
+
+    /// Draw the given text on the canvas.
+    fn draw_text(&mut self, text: &Text);
+
+    /// Finish drawing the current frame and present it on the screen.
+    fn present(&mut self);
+
+    /// Resize the canvas to match the current size of the output.
+    fn resize(&mut self) {}
+}
+
+/// Get the offset for a view with the given aspect ratio inside the given output size.
+///
+/// Returned value contains the width of the horizontal and the height of the vertical letterbox bars.
+pub fn aspect_offset(width: u32, height: u32, view_aspect: f32) -> (f32, f32) {
+    let mut result = (0.0, 0.0);
+    let x = width as f32;
+    let y = height as f32;
+    let aspect = x / y;
+    if (aspect - view_aspect).abs() > 0.0 {
+        if aspect > view_aspect {
+            result.0 = (x - y * view_aspect) / 2.0;
+        } else {
+            result.1 = (y - x / view_aspect) / 2.0;
+        }
+    }
+    result
+}
+
+/// Map the given position in output pixels into the view coordinates where the view is one unit wide
+/// and high.
+///
+/// Positions over the letterbox bars are mapped outside of the [0, 1] range, while nothing is
+/// returned for an output without a visible view.
+pub fn window_to_view(
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    view_aspect: f32,
+) -> Option<(f32, f32)> {
+    let (offset_x, offset_y) = aspect_offset(width, height, view_aspect);
+    let view_width = width as f32 - offset_x * 2.0;
+    let view_height = height as f32 - offset_y * 2.0;
+    if view_width <= 0.0 || view_height <= 0.0 {
+        return None;
+    }
+    Some((
+        (x as f32 - offset_x) / view_width,
+        (y as f32 - offset_y) / view_height,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pillarbox_corners_map_to_view_corners() {
+        // A wide window has bars of 280 pixels on both sides of a 1040 pixels wide view.
+        assert_eq!(window_to_view(280, 0, 1600, 800, 1.3), Some((0.0, 0.0)));
+        assert_eq!(window_to_view(1320, 800, 1600, 800, 1.3), Some((1.0, 1.0)));
+        assert_eq!(window_to_view(800, 400, 1600, 800, 1.3), Some((0.5, 0.5)));
+    }
+
+    #[test]
+    fn letterbox_corners_map_to_view_corners() {
+        // A tall window has bars of 100 pixels above and below an 800 pixels high view.
+        assert_eq!(window_to_view(0, 100, 1040, 1000, 1.3), Some((0.0, 0.0)));
+        assert_eq!(window_to_view(1040, 900, 1040, 1000, 1.3), Some((1.0, 1.0)));
+    }
+
+    #[test]
+    fn clicks_in_the_bars_map_outside_of_the_view() {
+        let (x, y) = window_to_view(100, 400, 1600, 800, 1.3).unwrap();
+        assert!(x < 0.0 && (0.0..=1.0).contains(&y));
+        let (x, y) = window_to_view(1500, 400, 1600, 800, 1.3).unwrap();
+        assert!(x > 1.0 && (0.0..=1.0).contains(&y));
+        let (x, y) = window_to_view(520, 50, 1040, 1000, 1.3).unwrap();
+        assert!((0.0..=1.0).contains(&x) && y < 0.0);
+        let (x, y) = window_to_view(520, 950, 1040, 1000, 1.3).unwrap();
+        assert!((0.0..=1.0).contains(&x) && y > 1.0);
+    }
+
+    #[test]
+    fn window_without_a_view_maps_nothing() {
+        assert_eq!(window_to_view(0, 0, 0, 0, 1.3), None);
+        assert_eq!(window_to_view(10, 10, 100, 0, 1.3), None);
+    }
+}
@@ -0,0 +1,453 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::{
+    codec::{invalid, read_bytes},
+    events::{GameEvent, Subscriber},
+    input::Player,
+    network::{Connection, Message, Netcode},
+    random::Random,
+    recording::Recording,
+    scenes::{Court, MatchSettings},
+    timer::STEP,
+};
+
+/// The bytes which identify a packet of a rollback session.
+const MAGIC: &[u8; 4] = b"PGRB";
+
+/// The version of the rollback protocol which must be the same on both peers.
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// The maximum amount of steps the local simulation may run ahead of the confirmed remote inputs.
+///
+/// The session waits for the remote peer when it falls further behind, which limits the amount of
+/// steps that are simulated again on a rollback.
+pub const MAX_PREDICTION: u32 = 12;
+
+/// The maximum amount of inputs which are sent in a single packet.
+const MAX_INPUTS: usize = 64;
+
+/// A way to deliver unreliable and unordered packets between the two peers of a session.
+pub trait Transport {
+    /// Send the given packet to the remote peer.
+    fn send(&mut self, packet: &[u8]) -> io::Result<()>;
+
+    /// Receive the packets which have arrived from the remote peer since the previous call.
+    fn receive(&mut self) -> io::Result<Vec<Vec<u8>>>;
+}
+
+/// The packets which travel in one direction of a loopback connection.
+#[derive(Default)]
+struct Channel {
+    /// The amount of times the receiving end has been polled.
+    polls: u64,
+    /// The packets and the polls of the receiving end on which they arrive.
+    packets: Vec<(u64, Vec<u8>)>,
+}
+
+/// A transport which delivers the packets within the same process with an artificial delay.
+///
+/// The delay is counted in the polls of the receiving end, where each poll is one simulation step,
+/// so the delivery is deterministic for a given seed.
+pub struct Loopback {
+    outgoing: Rc<RefCell<Channel>>,
+    incoming: Rc<RefCell<Channel>>,
+    latency: u64,
+    jitter: u64,
+    random: Random,
+}
+
+impl Loopback {
+    /// Build the two connected ends of a loopback connection.
+    ///
+    /// Each packet is delayed by the given latency and a random extra delay up to the given jitter,
+    /// which also lets the packets arrive in a different order than they were sent.
+    pub fn pair(latency: Duration, jitter: Duration, seed: u64) -> (Self, Self) {
+        let a = Rc::new(RefCell::new(Channel::default()));
+        let b = Rc::new(RefCell::new(Channel::default()));
+        let latency = steps(latency);
+        let jitter = steps(jitter);
+        let mut random = Random::new(seed);
+        let first = Self {
+            outgoing: a.clone(),
+            incoming: b.clone(),
+            latency,
+            jitter,
+            random: Random::new(random.next_u64()),
+        };
+        let second = Self {
+            outgoing: b,
+            incoming: a,
+            latency,
+            jitter,
+            random: Random::new(random.next_u64()),
+        };
+        (first, second)
+    }
+}
+
+impl Transport for Loopback {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        let jitter = self.random.next_u64() % (self.jitter + 1);
+        let mut channel = self.outgoing.borrow_mut();
+        let arrival = channel.polls + self.latency + jitter;
+        channel.packets.push((arrival, packet.to_vec()));
+        Ok(())
+    }
+
+    fn receive(&mut self) -> io::Result<Vec<Vec<u8>>> {
+        let mut channel = self.incoming.borrow_mut();
+        channel.polls += 1;
+        let polls = channel.polls;
+        let (arrived, waiting) = channel
+            .packets
+            .drain(..)
+            .partition(|(arrival, _)| *arrival <= polls);
+        channel.packets = waiting;
+        Ok(arrived.into_iter().map(|(_, packet)| packet).collect())
+    }
+}
+
+/// A transport which exchanges the packets of a session over the connection of a network match.
+///
+/// The messages of the network match which arrive on the same connection are handled on the side.
+/// The host answers each hello of the client with the welcome again, as the first welcome may have
+/// been lost, and a bye tells that the other player has left.
+pub struct UdpTransport {
+    connection: Connection,
+    /// The welcome which the host sends again when the client keeps saying hello.
+    welcome: Option<Message>,
+    left: bool,
+}
+
+impl UdpTransport {
+    /// Exchange the packets with the client of the given connection which was welcomed to a match
+    /// with the given settings.
+    pub fn host(connection: Connection, settings: MatchSettings) -> Self {
+        Self {
+            connection,
+            welcome: Some(Message::Welcome(settings, Netcode::Rollback)),
+            left: false,
+        }
+    }
+
+    /// Exchange the packets with the host of the given connection.
+    pub fn client(connection: Connection) -> Self {
+        Self {
+            connection,
+            welcome: None,
+            left: false,
+        }
+    }
+
+    /// Get the connection to the other player.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Check whether the other player has said that they left the match.
+    pub fn has_left(&self) -> bool {
+        self.left
+    }
+
+    /// Tell the other player that this player leaves the match.
+    pub fn leave(&mut self) -> io::Result<()> {
+        self.connection.send(&Message::Bye)
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.connection.send_packet(packet)
+    }
+
+    fn receive(&mut self) -> io::Result<Vec<Vec<u8>>> {
+        let (messages, packets) = self.connection.receive_all()?;
+        for message in messages {
+            match (message, self.welcome) {
+                (Message::Hello, Some(welcome)) => self.connection.send(&welcome)?,
+                (Message::Bye, _) => self.left = true,
+                _ => (),
+            }
+        }
+        Ok(packets)
+    }
+}
+
+/// A match where both peers simulate the court locally and roll back on mispredicted inputs.
+///
+/// The inputs of the remote player are predicted to stay the same as the latest received input.
+/// When an input arrives which differs from the prediction, the court is restored to the state
+/// before that step and the steps since then are simulated again with the received inputs. The
+/// simulation is deterministic, so both peers end up in the same state once all inputs have arrived.
+pub struct Session<T: Transport> {
+    transport: T,
+    local: Player,
+    /// The court after all simulated steps, which may depend on predicted inputs.
+    court: Court,
+    /// The amount of simulated steps.
+    frame: u32,
+    /// The inputs of the local player for each simulated step.
+    local_inputs: Vec<f32>,
+    /// The received inputs of the remote player without gaps from the first step.
+    remote_inputs: Vec<f32>,
+    /// The inputs of the remote player which were used to simulate each step.
+    used_inputs: Vec<f32>,
+    /// The amount of local inputs which the remote peer has received.
+    acknowledged: u32,
+    /// The saved courts before each step which may still be rolled back.
+    ///
+    /// The saved courts have an empty recording, as the recording of the current court is
+    /// truncated on a rollback instead of copying it on every step.
+    states: VecDeque<Court>,
+    /// The events of each step which may still be rolled back.
+    events: VecDeque<Vec<GameEvent>>,
+    /// The step of the first saved court.
+    first_state: u32,
+    rollbacks: u32,
+    /// The receivers of the events of the confirmed steps.
+    subscribers: Vec<Rc<RefCell<dyn Subscriber>>>,
+}
+
+impl<T: Transport> Session<T> {
+    /// Start a session of a match with the given settings where the given player is local.
+    pub fn new(settings: MatchSettings, local: Player, transport: T) -> Self {
+        Self {
+            transport,
+            local,
+            court: Court::new(settings),
+            frame: 0,
+            local_inputs: Vec::new(),
+            remote_inputs: Vec::new(),
+            used_inputs: Vec::new(),
+            acknowledged: 0,
+            states: VecDeque::new(),
+            events: VecDeque::new(),
+            first_state: 0,
+            rollbacks: 0,
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Notify the given subscriber of the events of the steps after they are confirmed.
+    ///
+    /// The events of the predicted steps may change on a rollback, so they are never published.
+    pub fn subscribe(&mut self, subscriber: Rc<RefCell<dyn Subscriber>>) {
+        self.subscribers.push(subscriber);
+    }
+
+    /// Get the court after all simulated steps, which is shown to the local player.
+    pub fn court(&self) -> &Court {
+        &self.court
+    }
+
+    /// Get the court after the steps whose inputs are known from both players.
+    ///
+    /// The confirmed court is the same on both peers for the same step.
+    pub fn confirmed(&self) -> &Court {
+        self.states.front().unwrap_or(&self.court)
+    }
+
+    /// Get the amount of simulated steps.
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Get the amount of steps whose inputs are known from both players.
+    pub fn confirmed_frame(&self) -> u32 {
+        self.frame.min(self.remote_inputs.len() as u32)
+    }
+
+    /// Get the recording of the inputs of the steps whose inputs are known from both players.
+    pub fn recording(&self) -> Recording {
+        let mut recording = self.court.recording().clone();
+        recording.truncate(self.confirmed_frame());
+        recording
+    }
+
+    /// Get the amount of times the simulation has been rolled back.
+    pub fn rollbacks(&self) -> u32 {
+        self.rollbacks
+    }
+
+    /// Get the transport to the remote peer.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Get the transport to the remote peer for changing it.
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// Exchange the inputs with the remote peer without simulating a step.
+    ///
+    /// This delivers the last inputs to the remote peer after the match is over.
+    pub fn poll(&mut self) -> io::Result<()> {
+        self.receive()?;
+        self.send()
+    }
+
+    /// Exchange the inputs with the remote peer and simulate the next step with the given movement
+    /// of the local paddle.
+    ///
+    /// Returns false when the session waits for the remote peer to catch up instead.
+    pub fn advance(&mut self, movement: f32) -> io::Result<bool> {
+        self.receive()?;
+        if self.frame - self.confirmed_frame() >= MAX_PREDICTION {
+            // Keep sending the inputs as the remote peer may be waiting for them.
+            self.send()?;
+            return Ok(false);
+        }
+        let movement = if movement.is_nan() {
+            0.0
+        } else {
+            movement.clamp(-1.0, 1.0)
+        };
+        self.local_inputs.push(movement);
+        let state = self.save_state();
+        self.states.push_back(state);
+        self.simulate(self.frame);
+        self.frame += 1;
+        self.send()?;
+        Ok(true)
+    }
+
+    /// Apply the received inputs and roll back the steps which were simulated with a wrong prediction.
+    fn receive(&mut self) -> io::Result<()> {
+        let known = self.remote_inputs.len();
+        for packet in self.transport.receive()? {
+            let (acknowledged, start, inputs) = match decode(&packet) {
+                Ok(packet) => packet,
+                Err(_) => continue,
+            };
+            self.acknowledged = self.acknowledged.max(acknowledged.min(self.frame));
+            let start = start as usize;
+            let length = self.remote_inputs.len();
+            if start <= length && start + inputs.len() > length {
+                self.remote_inputs
+                    .extend_from_slice(&inputs[length - start..]);
+            }
+        }
+        let simulated = self.frame as usize;
+        let mispredicted = (known..self.remote_inputs.len().min(simulated))
+            .find(|&step| self.remote_inputs[step] != self.used_inputs[step]);
+        if let Some(step) = mispredicted {
+            self.rollbacks += 1;
+            let mut recording = self.court.take_recording();
+            recording.truncate(step as u32);
+            self.court = self.states[step - self.first_state as usize].clone();
+            self.court.set_recording(recording);
+            for step in step as u32..self.frame {
+                self.states[(step - self.first_state) as usize] = self.save_state();
+                self.simulate(step);
+            }
+        }
+        // The confirmed steps can never be rolled back again, so their events are final.
+        while self.first_state < self.confirmed_frame() {
+            self.states.pop_front();
+            for event in self.events.pop_front().unwrap_or_default() {
+                for subscriber in &self.subscribers {
+                    subscriber.borrow_mut().notify(&event);
+                }
+            }
+            self.first_state += 1;
+        }
+        Ok(())
+    }
+
+    /// Copy the current court without its recording to simulate the next step again on a rollback.
+    fn save_state(&mut self) -> Court {
+        let recording = self.court.take_recording();
+        let state = self.court.clone();
+        self.court.set_recording(recording);
+        state
+    }
+
+    /// Send the local inputs which the remote peer has not received yet.
+    fn send(&mut self) -> io::Result<()> {
+        let start = self.acknowledged as usize;
+        let end = self.local_inputs.len().min(start + MAX_INPUTS);
+        let packet = encode(
+            self.remote_inputs.len() as u32,
+            start as u32,
+            &self.local_inputs[start..end],
+        );
+        self.transport.send(&packet)
+    }
+
+    /// Simulate the given step with the known or predicted inputs of the players.
+    fn simulate(&mut self, step: u32) {
+        let step = step as usize;
+        let remote = match self.remote_inputs.get(step) {
+            Some(input) => *input,
+            None => self.remote_inputs.last().copied().unwrap_or_default(),
+        };
+        match self.used_inputs.get_mut(step) {
+            Some(used) => *used = remote,
+            None => self.used_inputs.push(remote),
+        }
+        // The court stays as it is after the match, so both peers end up in the same final state.
+        if !self.court.is_over() {
+            let (left, right) = match self.local {
+                Player::One => (self.local_inputs[step], remote),
+                Player::Two => (remote, self.local_inputs[step]),
+            };
+            self.court.steer(Player::One, left);
+            self.court.steer(Player::Two, right);
+            self.court.update(STEP);
+        }
+        // The events are kept with their step and replaced when the step is simulated again.
+        let events = self.court.take_events();
+        match self.events.get_mut(step - self.first_state as usize) {
+            Some(slot) => *slot = events,
+            None => self.events.push_back(events),
+        }
+    }
+}
+
+/// Encode a packet which acknowledges the given amount of received inputs and contains the given
+/// inputs starting from the given step.
+fn encode(acknowledged: u32, start: u32, inputs: &[f32]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(14 + inputs.len() * 4);
+    packet.extend_from_slice(MAGIC);
+    packet.push(PROTOCOL_VERSION);
+    packet.extend_from_slice(&acknowledged.to_le_bytes());
+    packet.extend_from_slice(&start.to_le_bytes());
+    packet.push(inputs.len() as u8);
+    for input in inputs {
+        packet.extend_from_slice(&input.to_le_bytes());
+    }
+    packet
+}
+
+/// Decode the acknowledged amount of inputs, the first step and the inputs from the given packet.
+fn decode(packet: &[u8]) -> io::Result<(u32, u32, Vec<f32>)> {
+    let reader = &mut &packet[..];
+    if &read_bytes::<4>(reader)? != MAGIC || read_bytes::<1>(reader)? != [PROTOCOL_VERSION] {
+        return Err(invalid("not a packet of a rollback session"));
+    }
+    let acknowledged = u32::from_le_bytes(read_bytes(reader)?);
+    let start = u32::from_le_bytes(read_bytes(reader)?);
+    let [count] = read_bytes(reader)?;
+    let mut inputs = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let input = f32::from_le_bytes(read_bytes(reader)?);
+        if !(-1.0..=1.0).contains(&input) {
+            return Err(invalid("invalid movement"));
+        }
+        inputs.push(input);
+    }
+    if !reader.is_empty() {
+        return Err(invalid("unexpected data after the inputs"));
+    }
+    Ok((acknowledged, start, inputs))
+}
+
+/// Get the amount of whole simulation steps in the given duration.
+fn steps(duration: Duration) -> u64 {
+    (duration.as_nanos() / STEP.as_nanos()) as u64
+}
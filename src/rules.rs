@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+/// The rules which decide when a set and the whole match is over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchRules {
+    /// The amount of points which wins a set.
+    pub target_score: u8,
+    /// Whether a set must be won with a lead of at least two points.
+    pub win_by_two: bool,
+    /// The duration after which the match ends as soon as either player is leading.
+    pub time_limit: Option<Duration>,
+    /// The maximum amount of sets where winning the majority of sets wins the match.
+    pub sets: u8,
+}
+
+impl MatchRules {
+    /// The original rules where the first player with 10 points wins.
+    pub const CLASSIC: MatchRules = MatchRules {
+        target_score: 10,
+        win_by_two: false,
+        time_limit: None,
+        sets: 1,
+    };
+
+    /// The selectable rule presets with their names shown to the players.
+    pub const PRESETS: [(&'static str, MatchRules); 5] = [
+        ("CLASSIC", MatchRules::CLASSIC),
+        (
+            "SHORT",
+            MatchRules {
+                target_score: 5,
+                ..MatchRules::CLASSIC
+            },
+        ),
+        (
+            "DEUCE",
+            MatchRules {
+                target_score: 11,
+                win_by_two: true,
+                ..MatchRules::CLASSIC
+            },
+        ),
+        (
+            "TIMED",
+            MatchRules {
+                time_limit: Some(Duration::from_secs(180)),
+                ..MatchRules::CLASSIC
+            },
+        ),
+        (
+            "SETS",
+            MatchRules {
+                target_score: 5,
+                sets: 3,
+                ..MatchRules::CLASSIC
+            },
+        ),
+    ];
+
+    /// Get the name of the matching preset or CUSTOM if the rules do not match any preset.
+    pub fn name(&self) -> &'static str {
+        Self::PRESETS
+            .iter()
+            .find(|(_, rules)| rules == self)
+            .map_or("CUSTOM", |(name, _)| name)
+    }
+
+    /// Find the preset with the given name ignoring the case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::PRESETS
+            .iter()
+            .find(|(preset, _)| preset.eq_ignore_ascii_case(name))
+            .map(|(_, rules)| *rules)
+    }
+
+    /// Get the next preset or the first preset after the last one.
+    pub fn next(&self) -> Self {
+        let index = self.preset_index().map_or(0, |i| i + 1);
+        Self::PRESETS[index % Self::PRESETS.len()].1
+    }
+
+    /// Get the previous preset or the last preset before the first one.
+    pub fn previous(&self) -> Self {
+        let count = Self::PRESETS.len();
+        let index = self.preset_index().map_or(0, |i| i + count - 1);
+        Self::PRESETS[index % count].1
+    }
+
+    /// Describe the rules in a short text shown to the players.
+    pub fn describe(&self) -> String {
+        let mut parts = vec![format!("FIRST TO {}", self.target_score)];
+        if self.win_by_two {
+            parts.push("WIN BY TWO".to_string());
+        }
+        if self.sets > 1 {
+            parts.push(format!("BEST OF {}", self.sets));
+        }
+        if let Some(limit) = self.time_limit {
+            parts.push(format!("{} LIMIT", clock(limit)));
+        }
+        parts.join(", ")
+    }
+
+    /// Get the amount of sets a player must win to win the match.
+    pub fn sets_to_win(&self) -> u8 {
+        self.sets / 2 + 1
+    }
+
+    /// Check whether a player with the given points has won the set against the opponent.
+    ///
+    /// A lead of one point is enough at the highest possible score, so that a set always ends.
+    pub fn wins_set(&self, points: u8, opponent: u8) -> bool {
+        points >= self.target_score && (!self.win_by_two || points >= opponent.saturating_add(2))
+    }
+
+    /// Find the index of the preset which matches the rules.
+    fn preset_index(&self) -> Option<usize> {
+        Self::PRESETS.iter().position(|(_, rules)| rules == self)
+    }
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self::CLASSIC
+    }
+}
+
+/// Format the given duration as minutes and seconds.
+pub fn clock(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deuce_needs_a_lead_of_two_points() {
+        let rules = MatchRules::from_name("deuce").unwrap();
+        assert!(!rules.wins_set(10, 5));
+        assert!(rules.wins_set(11, 9));
+        assert!(!rules.wins_set(12, 11));
+        assert!(rules.wins_set(13, 11));
+    }
+
+    #[test]
+    fn deuce_ends_at_the_highest_score() {
+        let rules = MatchRules::from_name("deuce").unwrap();
+        assert!(!rules.wins_set(254, 253));
+        assert!(rules.wins_set(u8::MAX, 254));
+        assert!(rules.wins_set(u8::MAX, u8::MAX));
+    }
+}
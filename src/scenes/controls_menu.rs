@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use crate::{
+    bindings::Bindings,
+    context::Context,
+    geometry::{Text, TextSize},
+    input::{Action, Key},
+    options::Options,
+    renderer::Renderer,
+    scenes::menu::Menu,
+    scenes::{Scene, Transition},
+};
+
+/// The amount of items after the actions i.e. the reset and back items.
+const EXTRA_ITEMS: usize = 2;
+
+/// The menu where players can bind the keys to the actions of the game.
+pub struct ControlsMenu {
+    topic: Text,
+    help: Text,
+    menu: Menu,
+    /// The options whose bindings are being changed.
+    options: Options,
+    /// The action which is bound to the next released key.
+    waiting: Option<Action>,
+}
+
+impl ControlsMenu {
+    pub fn new() -> Self {
+        let sizes = (0..Action::ALL.len() + EXTRA_ITEMS).map(|_| TextSize::Tiny);
+        let mut menu = Self {
+            topic: Text {
+                x: 0.5,
+                y: 0.12,
+                text: "CONTROLS".encode_utf16().collect(),
+                size: TextSize::Big,
+            },
+            help: Text {
+                x: 0.5,
+                y: 0.23,
+                text: Vec::new(),
+                size: TextSize::Tiny,
+            },
+            menu: Menu::new(0.3, 0.05, sizes),
+            options: Options::default(),
+            waiting: None,
+        };
+        menu.refresh_labels();
+        menu.show_help();
+        menu
+    }
+
+    /// Update the texts of the menu items to match the current bindings.
+    fn refresh_labels(&mut self) {
+        for (i, action) in Action::ALL.iter().enumerate() {
+            let key = match self.waiting {
+                Some(waiting) if waiting == *action => "...",
+                _ => self.options.bindings.key(*action).label(),
+            };
+            self.menu
+                .set_label(i, format!("{}: {}", action.label(), key));
+        }
+        self.menu.set_label(Action::ALL.len(), "RESET DEFAULTS");
+        self.menu.set_label(Action::ALL.len() + 1, "BACK");
+    }
+
+    /// Show the instructions of how to use the menu.
+    fn show_help(&mut self) {
+        self.help.set_text(format!(
+            "Press {} to change a key and {} to go back.",
+            self.options.bindings.key(Action::Confirm).label(),
+            self.options.bindings.key(Action::Back).label()
+        ));
+    }
+
+    /// Bind the given key to the action waiting for a key unless it's already used.
+    fn bind(&mut self, action: Action, key: Key) {
+        self.waiting = None;
+        match self.options.bindings.bind(action, key) {
+            Ok(()) => self.show_help(),
+            Err(other) => self.help.set_text(format!(
+                "{} is already bound to {}.",
+                key.label(),
+                other.label()
+            )),
+        }
+        self.refresh_labels();
+    }
+
+    /// Save the bindings and return to the previous scene.
+    fn close(&self) -> Transition {
+        Transition::Save(Box::new(self.options), Box::new(Transition::Pop))
+    }
+}
+
+impl Default for ControlsMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scene for ControlsMenu {
+    fn tick(&mut self, _dt: Duration) -> Transition {
+        Transition::Stay
+    }
+
+    fn draw(&self, ctx: &mut dyn Renderer, _alpha: f32) {
+        ctx.draw_text(&self.topic);
+        ctx.draw_text(&self.help);
+        self.menu.draw(ctx);
+    }
+
+    fn action_down(&mut self, _action: Action) -> Transition {
+        Transition::Stay
+    }
+
+    fn action_up(&mut self, action: Action) -> Transition {
+        let selection = self.menu.selection();
+        match action {
+            Action::MenuUp => self.menu.move_selection(-1),
+            Action::MenuDown => self.menu.move_selection(1),
+            Action::Confirm => match Action::ALL.get(selection) {
+                Some(action) => {
+                    self.waiting = Some(*action);
+                    self.help
+                        .set_text(format!("Press a key for {}.", action.label()));
+                    self.refresh_labels();
+                }
+                None if selection == Action::ALL.len() => {
+                    self.options.bindings = Bindings::default();
+                    self.show_help();
+                    self.refresh_labels();
+                }
+                None => return self.close(),
+            },
+            Action::Back => return self.close(),
+            _ => (),
+        }
+        Transition::Stay
+    }
+
+    fn mouse_move(&mut self, x: f32, y: f32) -> Transition {
+        // The selection is kept on the action which is waiting for a key.
+        if self.waiting.is_none() {
+            self.menu.hover(x, y);
+        }
+        Transition::Stay
+    }
+
+    fn mouse_click(&mut self, x: f32, y: f32) -> Transition {
+        if self.waiting.is_none() && self.menu.hover(x, y) {
+            self.action_up(Action::Confirm)
+        } else {
+            Transition::Stay
+        }
+    }
+
+    fn enter(&mut self, context: &Context) {
+        self.options = context.options;
+        self.refresh_labels();
+        self.show_help();
+    }
+
+    fn capture_key(&mut self, key: Key) -> Option<Transition> {
+        let action = self.waiting?;
+        self.bind(action, key);
+        Some(Transition::Stay)
+    }
+}
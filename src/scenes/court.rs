@@ -1,33 +1,279 @@
+use std::cell::RefCell;
+use std::f32::consts::{FRAC_PI_3, SQRT_2};
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::rc::Rc;
 use std::time::Duration;
 
-use windows::Win32::UI::Input::KeyboardAndMouse::*;
-
 use crate::{
-    geometry::{Rectangle, Text, TextSize},
-    graphics::Graphics,
-    scenes::EndGame,
-    scenes::Scene,
+    ai::{Ai, Difficulty, ReferenceBot, View},
+    audio::Sound,
+    events::{EventBus, GameEvent, Subscriber},
+    geometry::{Hit, Rectangle, Text, TextSize},
+    input::{Action, ActionEvent, Player},
+    random::Random,
+    recording::{Recording, LAST_MATCH},
+    renderer::Renderer,
+    rules::{clock, MatchRules},
+    scenes::{EndGame, Pause, Scene, Transition},
+    statistics::Statistics,
 };
 
-/// A constant for the paddle movement velocity.
+/// The default height of the paddles.
+const PADDLE_HEIGHT: f32 = 0.15;
+
+/// The default paddle movement velocity.
 const PADDLE_VELOCITY: f32 = 0.001;
 
-/// Initial ball movement velocity.
-const BALL_VELOCITY: f32 = 0.0003;
+/// The default initial ball movement speed.
+const BALL_VELOCITY: f32 = 0.0003 * SQRT_2;
 
-/// A scalar to speed up ball velocity on each paddle hit.
+/// The default scalar to speed up ball velocity on each paddle hit.
 const BALL_VELOCITY_SCALAR: f32 = 1.1;
 
-/// The maximum movement velocity for the ball.
-const BALL_MAX_VELOCITY: f32 = 0.0006;
+/// The default maximum movement speed for the ball.
+const BALL_MAX_VELOCITY: f32 = 0.0006 * SQRT_2;
 
-/// The duration of the countdown at the beginning of each round.
+/// The default maximum angle (in radians) of the ball movement after it hits the edge of a paddle.
+const MAX_BOUNCE_ANGLE: f32 = FRAC_PI_3;
+
+/// The default duration of the countdown at the beginning of each round.
 const COUNTDOWN: Duration = Duration::from_millis(500);
 
-/// The amount of additional push added to collision handling.
+/// The default amount of additional push added to collision handling.
 const NUDGE: f32 = 0.001;
 
+/// The height of the top and the bottom wall.
+const WALL_HEIGHT: f32 = 0.03;
+
+/// The maximum amount of ball collisions which are resolved during a single time step.
+const MAX_COLLISIONS: usize = 8;
+
+/// The selection of which paddles are controlled by humans and which by the computer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// The left paddle is controlled by the computer and the right paddle by the reference bot.
+    Demo,
+    /// The left paddle is controlled by a human and the right paddle by the computer.
+    OnePlayer,
+    /// Both paddles are controlled by humans.
+    TwoPlayers,
+}
+
+/// The adjustable physical properties of the ball and the paddles.
+///
+/// Velocities are in court units per millisecond where the court is one unit high and wide.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Physics {
+    pub paddle_velocity: f32,
+    pub paddle_height: f32,
+    pub ball_velocity: f32,
+    pub ball_velocity_scalar: f32,
+    pub ball_max_velocity: f32,
+    /// The maximum angle (in radians) of the ball movement after it hits the edge of a paddle.
+    pub max_bounce_angle: f32,
+    /// The duration of the countdown at the beginning of each round.
+    pub countdown: Duration,
+    /// The amount of additional push added to collision handling.
+    pub nudge: f32,
+}
+
+impl Physics {
+    /// The valid range of the paddle velocity.
+    pub const PADDLE_VELOCITY_RANGE: RangeInclusive<f32> = 0.0001..=0.01;
+
+    /// The valid range of the paddle height, which is also limited by the space between the walls.
+    pub const PADDLE_HEIGHT_RANGE: RangeInclusive<f32> = 0.005..=1.0;
+
+    /// The valid range of the initial and the maximum ball velocity.
+    pub const BALL_VELOCITY_RANGE: RangeInclusive<f32> = 0.000025..=0.0075;
+
+    /// The valid range of the scalar of the ball velocity on each paddle hit.
+    pub const BALL_VELOCITY_SCALAR_RANGE: RangeInclusive<f32> = 1.0..=2.0;
+
+    /// The valid range of the maximum bounce angle in degrees.
+    pub const MAX_BOUNCE_DEGREES_RANGE: RangeInclusive<f32> = 0.0..=80.0;
+
+    /// The valid range of the countdown.
+    pub const COUNTDOWN_RANGE: RangeInclusive<Duration> = Duration::ZERO..=Duration::from_secs(10);
+
+    /// The valid range of the collision nudge.
+    pub const NUDGE_RANGE: RangeInclusive<f32> = 0.0..=0.01;
+
+    /// Get the height of the tallest paddle which fits between the walls with this nudge.
+    pub fn max_paddle_height(&self) -> f32 {
+        1.0 - 2.0 * WALL_HEIGHT - 2.0 * self.nudge
+    }
+
+    /// Check that the properties are within their valid ranges and consistent with each other.
+    ///
+    /// The court relies on the valid properties, e.g. the paddles must fit between the walls.
+    pub fn validate(&self) -> Result<(), String> {
+        // The angle is compared in radians, which accepts the exact conversion of the largest angle.
+        let degrees = Self::MAX_BOUNCE_DEGREES_RANGE;
+        let radians = degrees.start().to_radians()..=degrees.end().to_radians();
+        let ranges = [
+            (
+                "paddle_velocity",
+                self.paddle_velocity,
+                Self::PADDLE_VELOCITY_RANGE,
+            ),
+            (
+                "paddle_height",
+                self.paddle_height,
+                Self::PADDLE_HEIGHT_RANGE,
+            ),
+            (
+                "ball_velocity",
+                self.ball_velocity,
+                Self::BALL_VELOCITY_RANGE,
+            ),
+            (
+                "ball_max_velocity",
+                self.ball_max_velocity,
+                Self::BALL_VELOCITY_RANGE,
+            ),
+            (
+                "ball_velocity_scalar",
+                self.ball_velocity_scalar,
+                Self::BALL_VELOCITY_SCALAR_RANGE,
+            ),
+            ("max_bounce_angle", self.max_bounce_angle, radians),
+            ("nudge", self.nudge, Self::NUDGE_RANGE),
+        ];
+        for (name, value, range) in ranges {
+            if !range.contains(&value) {
+                return Err(format!(
+                    "{} must be from {} to {}",
+                    name,
+                    range.start(),
+                    range.end()
+                ));
+            }
+        }
+        if !Self::COUNTDOWN_RANGE.contains(&self.countdown) {
+            return Err(format!(
+                "countdown must be at most {} milliseconds",
+                Self::COUNTDOWN_RANGE.end().as_millis()
+            ));
+        }
+        if self.ball_max_velocity < self.ball_velocity {
+            return Err("ball_max_velocity must not be lower than ball_velocity".to_string());
+        }
+        if self.paddle_height > self.max_paddle_height() {
+            return Err(
+                "paddle_height must leave room for the nudge between the walls".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Physics {
+            paddle_velocity: PADDLE_VELOCITY,
+            paddle_height: PADDLE_HEIGHT,
+            ball_velocity: BALL_VELOCITY,
+            ball_velocity_scalar: BALL_VELOCITY_SCALAR,
+            ball_max_velocity: BALL_MAX_VELOCITY,
+            max_bounce_angle: MAX_BOUNCE_ANGLE,
+            countdown: COUNTDOWN,
+            nudge: NUDGE,
+        }
+    }
+}
+
+/// The settings which define how a match on the court is played.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MatchSettings {
+    pub mode: Mode,
+    pub difficulty: Difficulty,
+    pub rules: MatchRules,
+    pub physics: Physics,
+    pub seed: u64,
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        MatchSettings {
+            mode: Mode::Demo,
+            difficulty: Difficulty::Normal,
+            rules: MatchRules::CLASSIC,
+            physics: Physics::default(),
+            seed: 0,
+        }
+    }
+}
+
+/// The state of a match which is enough to show the court without simulating it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Snapshot {
+    pub steps: u32,
+    pub elapsed: Duration,
+    pub countdown: Duration,
+    pub ball_x: f32,
+    pub ball_y: f32,
+    pub ball_x_movement: f32,
+    pub ball_y_movement: f32,
+    pub l_paddle_y: f32,
+    pub r_paddle_y: f32,
+    pub l_points: u8,
+    pub r_points: u8,
+    pub l_sets: u8,
+    pub r_sets: u8,
+}
+
+impl Snapshot {
+    /// Calculate a 64-bit FNV-1a checksum of the state, which is the same for equal states.
+    pub fn checksum(&self) -> u64 {
+        let fields = [
+            &self.steps.to_le_bytes()[..],
+            &self.elapsed.as_nanos().to_le_bytes(),
+            &self.countdown.as_nanos().to_le_bytes(),
+            &self.ball_x.to_le_bytes(),
+            &self.ball_y.to_le_bytes(),
+            &self.ball_x_movement.to_le_bytes(),
+            &self.ball_y_movement.to_le_bytes(),
+            &self.l_paddle_y.to_le_bytes(),
+            &self.r_paddle_y.to_le_bytes(),
+            &[self.l_points, self.r_points, self.l_sets, self.r_sets],
+        ];
+        fields
+            .iter()
+            .flat_map(|bytes| bytes.iter())
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+}
+
+/// The side of the court.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// An entity on the court which the ball can collide with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Obstacle {
+    Wall,
+    Paddle(Side),
+}
+
+/// A source of movement for a paddle on the court.
+#[derive(Clone, Debug)]
+enum Controller {
+    Human,
+    Computer(Ai),
+    Reference(ReferenceBot),
+}
+
 /// The scene where players compete between each other.
+///
+/// The court can be cloned to save its whole state, which is used to roll back the simulation.
+#[derive(Clone)]
 pub struct Court {
     ball: Rectangle,
     l_paddle: Rectangle,
@@ -36,6 +282,7 @@ pub struct Court {
     b_wall: Rectangle,
     l_score: Text,
     r_score: Text,
+    status: Text,
 
     ball_x_movement: f32,
     ball_y_movement: f32,
@@ -43,44 +290,81 @@ pub struct Court {
     l_movement: f32,
     r_movement: f32,
 
+    /// The vertical positions which the human controlled paddles follow with a pointer.
+    l_aim: Option<f32>,
+    r_aim: Option<f32>,
+
+    l_controller: Controller,
+    r_controller: Controller,
+
     l_points: u8,
     r_points: u8,
+    l_sets: u8,
+    r_sets: u8,
+
+    prev_ball: Rectangle,
+    prev_l_paddle: Rectangle,
+    prev_r_paddle: Rectangle,
 
     countdown: Duration,
+    elapsed: Duration,
+    settings: MatchSettings,
+    steps: u32,
+    recording: Recording,
+    /// Whether the ball has been served after the latest countdown.
+    served: bool,
+    events: EventBus,
+    statistics: Statistics,
 }
 
 impl Court {
-    pub fn new() -> Self {
-        Court {
-            ball: Rectangle {
-                x: 0.5 - (0.025 / 2.0),
-                y: 0.5 - (0.0325 / 2.0),
-                w: 0.025,
-                h: 0.0325,
-            },
-            l_paddle: Rectangle {
-                x: 0.05,
-                y: 0.5 - 0.075,
-                w: 0.025,
-                h: 0.15,
-            },
-            r_paddle: Rectangle {
-                x: 1.0 - 0.025 - 0.05,
-                y: 0.5 - (0.15 / 2.0),
-                w: 0.025,
-                h: 0.15,
-            },
+    pub fn new(settings: MatchSettings) -> Self {
+        let mut random = Random::new(settings.seed);
+        let (l_controller, r_controller) = match settings.mode {
+            Mode::Demo => (
+                Controller::Computer(Ai::new(random.next_u64(), settings.difficulty)),
+                Controller::Reference(ReferenceBot),
+            ),
+            Mode::OnePlayer => (
+                Controller::Human,
+                Controller::Computer(Ai::new(random.next_u64(), settings.difficulty)),
+            ),
+            Mode::TwoPlayers => (Controller::Human, Controller::Human),
+        };
+        let ball = Rectangle {
+            x: 0.5 - (0.025 / 2.0),
+            y: 0.5 - (0.0325 / 2.0),
+            w: 0.025,
+            h: 0.0325,
+        };
+        let paddle_height = settings.physics.paddle_height;
+        let l_paddle = Rectangle {
+            x: 0.05,
+            y: 0.5 - (paddle_height / 2.0),
+            w: 0.025,
+            h: paddle_height,
+        };
+        let r_paddle = Rectangle {
+            x: 1.0 - 0.025 - 0.05,
+            y: 0.5 - (paddle_height / 2.0),
+            w: 0.025,
+            h: paddle_height,
+        };
+        let mut court = Court {
+            ball,
+            l_paddle,
+            r_paddle,
             t_wall: Rectangle {
                 x: 0.0,
                 y: 0.0,
                 w: 1.0,
-                h: 0.03,
+                h: WALL_HEIGHT,
             },
             b_wall: Rectangle {
                 x: 0.0,
-                y: 1.0 - 0.03,
+                y: 1.0 - WALL_HEIGHT,
                 w: 1.0,
-                h: 0.03,
+                h: WALL_HEIGHT,
             },
             l_score: Text {
                 x: 0.35,
@@ -94,23 +378,524 @@ impl Court {
                 text: "0".encode_utf16().collect(),
                 size: TextSize::Big,
             },
-            ball_x_movement: BALL_VELOCITY,
-            ball_y_movement: -BALL_VELOCITY,
+            status: Text {
+                x: 0.5,
+                y: 0.27,
+                text: Vec::new(),
+                size: TextSize::Tiny,
+            },
+            ball_x_movement: settings.physics.ball_velocity / SQRT_2,
+            ball_y_movement: -settings.physics.ball_velocity / SQRT_2,
             l_movement: 0.0,
             l_points: 0,
             r_movement: 0.0,
             r_points: 0,
-            countdown: COUNTDOWN,
+            l_aim: None,
+            r_aim: None,
+            l_sets: 0,
+            r_sets: 0,
+            l_controller,
+            r_controller,
+            prev_ball: ball,
+            prev_l_paddle: l_paddle,
+            prev_r_paddle: r_paddle,
+            countdown: settings.physics.countdown,
+            elapsed: Duration::ZERO,
+            settings,
+            steps: 0,
+            recording: Recording::new(settings),
+            served: false,
+            events: EventBus::default(),
+            statistics: Statistics::default(),
+        };
+        court.refresh_status();
+        court
+    }
+
+    /// Update the court logic and physics simulation with the given time step.
+    pub fn update(&mut self, dt: Duration) {
+        let was_over = self.is_over();
+        self.save_positions();
+        self.steps += 1;
+        self.elapsed += dt;
+        self.refresh_status();
+
+        // Skip physics if countdown is still in progress.
+        self.countdown -= Duration::min(self.countdown, dt);
+        if !self.countdown.is_zero() {
+            return;
+        }
+        if !self.served {
+            self.served = true;
+            self.publish(GameEvent::ServeStarted);
+        }
+        self.control_paddles(dt);
+        self.move_paddles(dt);
+        self.move_ball(dt);
+
+        // Check whether ball hits the goals.
+        if self.ball.x <= 0.0 {
+            self.clear_state();
+            self.score(Side::Right);
+        } else if (self.ball.x + self.ball.w) >= 1.0 {
+            self.clear_state();
+            self.score(Side::Left);
+        }
+        if !was_over && self.is_over() {
+            self.publish(GameEvent::MatchEnded);
+        }
+    }
+
+    /// Tell the court that the given action is being pressed.
+    pub fn press(&mut self, action: Action) {
+        let paddle = self.paddle_for(action);
+        if paddle.is_some() {
+            self.recording
+                .push(self.steps, ActionEvent::Pressed(action));
+        }
+        // The keys take over the paddle from the pointer until the pointer is moved again.
+        match paddle {
+            Some((Side::Left, up)) => {
+                self.l_movement = if up { -1.0 } else { 1.0 };
+                self.l_aim = None;
+            }
+            Some((Side::Right, up)) => {
+                self.r_movement = if up { -1.0 } else { 1.0 };
+                self.r_aim = None;
+            }
+            None => (),
+        }
+    }
+
+    /// Tell the court that the given action is being released.
+    pub fn release(&mut self, action: Action) {
+        let paddle = self.paddle_for(action);
+        if paddle.is_some() {
+            self.recording
+                .push(self.steps, ActionEvent::Released(action));
+        }
+        match paddle {
+            Some((Side::Left, true)) => self.l_movement = f32::max(self.l_movement, 0.0),
+            Some((Side::Left, false)) => self.l_movement = f32::min(self.l_movement, 0.0),
+            Some((Side::Right, true)) => self.r_movement = f32::max(self.r_movement, 0.0),
+            Some((Side::Right, false)) => self.r_movement = f32::min(self.r_movement, 0.0),
+            None => (),
+        }
+    }
+
+    /// Get the current points of the left and right player.
+    pub fn points(&self) -> (u8, u8) {
+        (self.l_points, self.r_points)
+    }
+
+    /// Get the amount of sets won by the left and right player.
+    pub fn sets(&self) -> (u8, u8) {
+        (self.l_sets, self.r_sets)
+    }
+
+    /// Get the amount of simulated time since the beginning of the match.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Get the settings of the match which is played on the court.
+    pub fn settings(&self) -> &MatchSettings {
+        &self.settings
+    }
+
+    /// Get the amount of simulation steps which have been run on the court.
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// Get the recording of the inputs which have been received by the court.
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    /// Notify the given subscriber of the events which happen on the court after this.
+    pub fn subscribe(&mut self, subscriber: Rc<RefCell<dyn Subscriber>>) {
+        self.events.subscribe(subscriber);
+    }
+
+    /// Take the events which have happened on the court since the events were taken last time.
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        self.events.take()
+    }
+
+    /// Take the recording of the inputs out of the court and leave an empty recording in its place.
+    pub fn take_recording(&mut self) -> Recording {
+        std::mem::replace(&mut self.recording, Recording::new(self.settings))
+    }
+
+    /// Replace the recording of the inputs which have been received by the court.
+    pub fn set_recording(&mut self, recording: Recording) {
+        self.recording = recording;
+    }
+
+    /// Get the statistics of the match so far.
+    pub fn statistics(&self) -> Statistics {
+        let mut statistics = self.statistics.clone();
+        statistics.duration = self.elapsed;
+        statistics
+    }
+
+    /// Collect the given event into the statistics and pass it to the subscribers.
+    fn publish(&mut self, event: GameEvent) {
+        self.statistics.notify(&event);
+        self.events.publish(event);
+    }
+
+    /// Check whether either player has won the match according to the match rules.
+    ///
+    /// After the time limit the match ends as soon as either player leads in sets or in points.
+    pub fn is_over(&self) -> bool {
+        let rules = &self.settings.rules;
+        let sets_to_win = rules.sets_to_win();
+        if self.l_sets >= sets_to_win || self.r_sets >= sets_to_win {
+            return true;
         }
+        self.time_is_up() && (self.l_sets, self.l_points) != (self.r_sets, self.r_points)
     }
 
-    /// Apply the movement for all dynamic entities based on the provided delta time.
-    fn apply_movement(&mut self, dt: Duration) {
-        let millis = dt.as_millis() as f32;
-        self.r_paddle.y += self.r_movement * PADDLE_VELOCITY * millis;
-        self.l_paddle.y += self.l_movement * PADDLE_VELOCITY * millis;
-        self.ball.y += self.ball_y_movement * millis;
-        self.ball.x += self.ball_x_movement * millis;
+    /// Check whether the time limit of the match has been reached.
+    fn time_is_up(&self) -> bool {
+        matches!(self.settings.rules.time_limit, Some(limit) if self.elapsed >= limit)
+    }
+
+    /// Give a point to the player on the given side and start a new set if the set is won.
+    fn score(&mut self, side: Side) {
+        let (points, opponent, sets) = match side {
+            Side::Left => (&mut self.l_points, self.r_points, &mut self.l_sets),
+            Side::Right => (&mut self.r_points, self.l_points, &mut self.r_sets),
+        };
+        *points = points.saturating_add(1);
+        if self.settings.rules.wins_set(*points, opponent) {
+            *sets += 1;
+            if !self.is_over() {
+                self.l_points = 0;
+                self.r_points = 0;
+            }
+        }
+        self.l_score.set_text(self.l_points);
+        self.r_score.set_text(self.r_points);
+        self.refresh_status();
+        self.publish(GameEvent::Goal { scorer: side });
+    }
+
+    /// Take a snapshot of the current state of the match.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            steps: self.steps,
+            elapsed: self.elapsed,
+            countdown: self.countdown,
+            ball_x: self.ball.x,
+            ball_y: self.ball.y,
+            ball_x_movement: self.ball_x_movement,
+            ball_y_movement: self.ball_y_movement,
+            l_paddle_y: self.l_paddle.y,
+            r_paddle_y: self.r_paddle.y,
+            l_points: self.l_points,
+            r_points: self.r_points,
+            l_sets: self.l_sets,
+            r_sets: self.r_sets,
+        }
+    }
+
+    /// Continue the match from the given snapshot.
+    ///
+    /// The current positions are kept as the previous positions to interpolate towards the snapshot,
+    /// and the events between the current state and the snapshot are guessed from the changes of
+    /// the score, the countdown and the ball direction.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        let was_over = self.is_over();
+        let events = self.guess_events(snapshot);
+        self.save_positions();
+        self.apply(snapshot);
+        self.statistics.approximate = true;
+        for event in events {
+            self.publish(event);
+        }
+        if !was_over && self.is_over() {
+            self.publish(GameEvent::MatchEnded);
+        }
+    }
+
+    /// Jump to the given snapshot without interpolating towards it or publishing any events.
+    ///
+    /// This is used for the first received state of a match, which may be far from the start of
+    /// the match, so that no goals or hits are guessed from the difference.
+    pub fn sync(&mut self, snapshot: &Snapshot) {
+        self.apply(snapshot);
+        self.save_positions();
+    }
+
+    /// Set the state of the match from the given snapshot.
+    fn apply(&mut self, snapshot: &Snapshot) {
+        self.steps = snapshot.steps;
+        self.elapsed = snapshot.elapsed;
+        self.countdown = snapshot.countdown;
+        self.ball.x = snapshot.ball_x;
+        self.ball.y = snapshot.ball_y;
+        self.ball_x_movement = snapshot.ball_x_movement;
+        self.ball_y_movement = snapshot.ball_y_movement;
+        self.l_paddle.y = snapshot.l_paddle_y;
+        self.r_paddle.y = snapshot.r_paddle_y;
+        self.l_points = snapshot.l_points;
+        self.r_points = snapshot.r_points;
+        self.l_sets = snapshot.l_sets;
+        self.r_sets = snapshot.r_sets;
+        self.served = self.countdown.is_zero();
+        self.l_score.set_text(self.l_points);
+        self.r_score.set_text(self.r_points);
+        self.refresh_status();
+    }
+
+    /// Guess the events which happened between the current state and the given snapshot.
+    fn guess_events(&self, snapshot: &Snapshot) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        let l_score = (snapshot.l_sets, snapshot.l_points) != (self.l_sets, self.l_points);
+        let r_score = (snapshot.r_sets, snapshot.r_points) != (self.r_sets, self.r_points);
+        if l_score || r_score {
+            // Both scores change when the points are cleared after a set.
+            let scorer = if snapshot.l_sets > self.l_sets || (l_score && !r_score) {
+                Side::Left
+            } else {
+                Side::Right
+            };
+            events.push(GameEvent::Goal { scorer });
+            return events;
+        }
+        if !self.countdown.is_zero() && snapshot.countdown.is_zero() {
+            events.push(GameEvent::ServeStarted);
+        }
+        let returned = snapshot.ball_x_movement.signum() != self.ball_x_movement.signum();
+        if returned {
+            let side = if snapshot.ball_x_movement > 0.0 {
+                Side::Left
+            } else {
+                Side::Right
+            };
+            let speed = snapshot.ball_x_movement.hypot(snapshot.ball_y_movement);
+            events.push(GameEvent::PaddleHit { side, speed });
+        }
+        // The paddles may also turn the ball vertically, so only the other turns are wall bounces.
+        if !returned && snapshot.ball_y_movement.signum() != self.ball_y_movement.signum() {
+            events.push(GameEvent::WallBounce);
+        }
+        events
+    }
+
+    /// Update the status text which shows the sets and the remaining time of the match.
+    fn refresh_status(&mut self) {
+        let rules = &self.settings.rules;
+        let mut parts = Vec::new();
+        if rules.sets > 1 {
+            parts.push(format!("SETS {} - {}", self.l_sets, self.r_sets));
+        }
+        if let Some(limit) = rules.time_limit {
+            parts.push(match limit.checked_sub(self.elapsed) {
+                Some(left) if !left.is_zero() => clock(left + Duration::from_millis(999)),
+                _ => "SUDDEN DEATH".to_string(),
+            });
+        }
+        self.status.set_text(parts.join("   "));
+    }
+
+    /// Let the paddle of the given player follow a pointer at the given vertical position.
+    pub fn aim(&mut self, player: Player, y: f32) {
+        let side = match self.side_for(player) {
+            Some(side) if y.is_finite() => side,
+            _ => return,
+        };
+        let aim = match side {
+            Side::Left => self.l_aim,
+            Side::Right => self.r_aim,
+        };
+        if aim == Some(y) {
+            return;
+        }
+        self.recording
+            .push(self.steps, ActionEvent::Aimed(player, y));
+        match side {
+            Side::Left => self.l_aim = Some(y),
+            Side::Right => self.r_aim = Some(y),
+        }
+    }
+
+    /// Move the paddle of the given player with the given speed from an analog axis in range [-1, 1].
+    ///
+    /// The axis takes over the paddle from the pointer until the pointer is moved again.
+    pub fn steer(&mut self, player: Player, value: f32) {
+        let side = match self.side_for(player) {
+            Some(side) if !value.is_nan() => side,
+            _ => return,
+        };
+        let value = value.clamp(-1.0, 1.0);
+        let movement = match side {
+            Side::Left => self.l_movement,
+            Side::Right => self.r_movement,
+        };
+        if movement == value {
+            return;
+        }
+        self.recording
+            .push(self.steps, ActionEvent::Steered(player, value));
+        match side {
+            Side::Left => {
+                self.l_movement = value;
+                self.l_aim = None;
+            }
+            Side::Right => {
+                self.r_movement = value;
+                self.r_aim = None;
+            }
+        }
+    }
+
+    /// Resolve the human controlled paddle and direction (up or not) which the given action moves.
+    fn paddle_for(&self, action: Action) -> Option<(Side, bool)> {
+        match action {
+            Action::MoveUp(player) => Some((self.side_for(player)?, true)),
+            Action::MoveDown(player) => Some((self.side_for(player)?, false)),
+            _ => None,
+        }
+    }
+
+    /// Resolve the side of the human controlled paddle of the given player.
+    ///
+    /// Both players control the left paddle when the right paddle is controlled by the computer.
+    fn side_for(&self, player: Player) -> Option<Side> {
+        let l_human = matches!(self.l_controller, Controller::Human);
+        let r_human = matches!(self.r_controller, Controller::Human);
+        match player {
+            Player::One if l_human => Some(Side::Left),
+            Player::Two if r_human => Some(Side::Right),
+            Player::Two if l_human => Some(Side::Left),
+            _ => None,
+        }
+    }
+
+    /// Decide the movement of the paddles which are controlled by the computer or by a pointer.
+    ///
+    /// The paddles which follow a pointer move towards it with their full speed.
+    fn control_paddles(&mut self, dt: Duration) {
+        let reach = self.settings.physics.paddle_velocity * dt.as_secs_f32() * 1000.0;
+        if let Some(aim) = self.l_aim {
+            let center = self.l_paddle.y + self.l_paddle.h / 2.0;
+            self.l_movement = ((aim - center) / reach).clamp(-1.0, 1.0);
+        }
+        if let Some(aim) = self.r_aim {
+            let center = self.r_paddle.y + self.r_paddle.h / 2.0;
+            self.r_movement = ((aim - center) / reach).clamp(-1.0, 1.0);
+        }
+        let paddles = [
+            (&mut self.l_controller, &self.l_paddle, &mut self.l_movement),
+            (&mut self.r_controller, &self.r_paddle, &mut self.r_movement),
+        ];
+        for (controller, paddle, movement) in paddles {
+            let view = View {
+                ball: &self.ball,
+                ball_x_movement: self.ball_x_movement,
+                ball_y_movement: self.ball_y_movement,
+                paddle,
+                top: self.t_wall.y + self.t_wall.h,
+                bottom: self.b_wall.y,
+                reach,
+            };
+            match controller {
+                Controller::Human => (),
+                Controller::Computer(ai) => *movement = ai.movement(&view, dt),
+                Controller::Reference(bot) => *movement = bot.movement(&view),
+            }
+        }
+    }
+
+    /// Move the paddles based on their movement and keep them between the walls.
+    fn move_paddles(&mut self, dt: Duration) {
+        let Physics {
+            paddle_velocity,
+            nudge,
+            ..
+        } = self.settings.physics;
+        let millis = dt.as_secs_f32() * 1000.0;
+        self.r_paddle.y += self.r_movement * paddle_velocity * millis;
+        self.l_paddle.y += self.l_movement * paddle_velocity * millis;
+
+        // A paddle which is taller than the court is kept at the top wall instead of panicking.
+        let top = self.t_wall.y + self.t_wall.h + nudge;
+        for paddle in [&mut self.l_paddle, &mut self.r_paddle] {
+            let bottom = self.b_wall.y - paddle.h - nudge;
+            paddle.y = paddle.y.min(bottom).max(top);
+        }
+    }
+
+    /// Move the ball along its path and resolve each collision in the order they happen.
+    ///
+    /// Collisions are detected with the swept paths of the ball so that it cannot pass through
+    /// the paddles or walls even with large time steps or velocities.
+    fn move_ball(&mut self, dt: Duration) {
+        let mut millis = dt.as_secs_f32() * 1000.0;
+        for _ in 0..MAX_COLLISIONS {
+            let dx = self.ball_x_movement * millis;
+            let dy = self.ball_y_movement * millis;
+            let Some((obstacle, hit)) = self.next_hit(dx, dy) else {
+                self.ball.x += dx;
+                self.ball.y += dy;
+                return;
+            };
+
+            // Move the ball to the collision point and reflect it from the hit surface.
+            let nudge = self.settings.physics.nudge;
+            self.ball.x += dx * hit.time + hit.normal_x * nudge;
+            self.ball.y += dy * hit.time + hit.normal_y * nudge;
+            millis *= 1.0 - hit.time;
+            let direction = self.ball_x_movement.signum();
+            match obstacle {
+                Obstacle::Paddle(side) if hit.normal_x != 0.0 => self.bounce_from_paddle(side),
+                _ if hit.normal_x != 0.0 => self.ball_x_movement = -self.ball_x_movement,
+                _ => self.ball_y_movement = -self.ball_y_movement,
+            }
+            self.accelerate_ball();
+            let speed = self.ball_x_movement.hypot(self.ball_y_movement);
+            match obstacle {
+                Obstacle::Paddle(side) if self.ball_x_movement.signum() != direction => {
+                    self.publish(GameEvent::PaddleHit { side, speed })
+                }
+                // Only the hits which return the ball count, as the ball may also glance off the
+                // ends or the backs of the paddles.
+                Obstacle::Paddle(_) => (),
+                Obstacle::Wall => self.publish(GameEvent::WallBounce),
+            }
+        }
+
+        // Spend the time which is left after the most collisions by moving up to the next obstacle.
+        let dx = self.ball_x_movement * millis;
+        let dy = self.ball_y_movement * millis;
+        let time = self.next_hit(dx, dy).map_or(1.0, |(_, hit)| hit.time);
+        self.ball.x += dx * time;
+        self.ball.y += dy * time;
+    }
+
+    /// Find the first obstacle which the ball hits when it moves by the given displacement.
+    fn next_hit(&self, dx: f32, dy: f32) -> Option<(Obstacle, Hit)> {
+        let obstacles = [
+            (Obstacle::Wall, &self.t_wall),
+            (Obstacle::Wall, &self.b_wall),
+            (Obstacle::Paddle(Side::Left), &self.l_paddle),
+            (Obstacle::Paddle(Side::Right), &self.r_paddle),
+        ];
+        obstacles
+            .into_iter()
+            .filter_map(|(obstacle, rect)| Some((obstacle, self.ball.sweep(dx, dy, rect)?)))
+            .min_by(|(_, a), (_, b)| a.time.total_cmp(&b.time))
+    }
+
+    /// Store the current positions of the moving entities for the render interpolation.
+    fn save_positions(&mut self) {
+        self.prev_ball = self.ball;
+        self.prev_l_paddle = self.l_paddle;
+        self.prev_r_paddle = self.r_paddle;
     }
 
     /// Clear the gameyard state by centering the ball and paddles and starting a new countdown.
@@ -119,113 +904,200 @@ impl Court {
         self.ball.y = 0.5 - (self.ball.h / 2.0);
         self.l_paddle.y = 0.5 - (self.l_paddle.h / 2.0);
         self.r_paddle.y = 0.5 - (self.r_paddle.h / 2.0);
-        self.countdown = COUNTDOWN;
-        self.ball_x_movement *= 1.0 / self.ball_x_movement * BALL_VELOCITY;
-        self.ball_y_movement *= 1.0 / self.ball_y_movement * BALL_VELOCITY;
+        self.countdown = self.settings.physics.countdown;
+        self.served = false;
+        self.ball_x_movement = self.settings.physics.ball_velocity / SQRT_2;
+        self.ball_y_movement = self.settings.physics.ball_velocity / SQRT_2;
+        self.save_positions();
+    }
+
+    /// Send the ball away from the paddle on the given side while preserving the ball speed.
+    ///
+    /// The outgoing angle grows with the distance between the ball and the paddle centers.
+    fn bounce_from_paddle(&mut self, side: Side) {
+        let (paddle, direction) = match side {
+            Side::Left => (&self.l_paddle, 1.0),
+            Side::Right => (&self.r_paddle, -1.0),
+        };
+        let distance = (self.ball.y + self.ball.h / 2.0) - (paddle.y + paddle.h / 2.0);
+        let offset = (distance / ((paddle.h + self.ball.h) / 2.0)).clamp(-1.0, 1.0);
+        let angle = offset * self.settings.physics.max_bounce_angle;
+        let speed = self.ball_x_movement.hypot(self.ball_y_movement);
+        self.ball_x_movement = direction * speed * angle.cos();
+        self.ball_y_movement = speed * angle.sin();
     }
 
     /// Increase the speed of the ball if the max speed is not yet reached.
     fn accelerate_ball(&mut self) {
-        self.ball_y_movement *= BALL_VELOCITY_SCALAR;
-        self.ball_x_movement *= BALL_VELOCITY_SCALAR;
-        self.ball_y_movement = f32::min(self.ball_y_movement, BALL_MAX_VELOCITY);
-        self.ball_x_movement = f32::min(self.ball_x_movement, BALL_MAX_VELOCITY);
+        let speed = self.ball_x_movement.hypot(self.ball_y_movement);
+        let Physics {
+            ball_velocity_scalar,
+            ball_max_velocity,
+            ..
+        } = self.settings.physics;
+        let scalar = (ball_max_velocity / speed).clamp(1.0, ball_velocity_scalar);
+        self.ball_x_movement *= scalar;
+        self.ball_y_movement *= scalar;
     }
 }
 
 impl Scene for Court {
-    fn tick(mut self: Box<Self>, dt: Duration) -> Option<Box<dyn Scene>> {
-        // Skip physics if countdown is still in progress.
-        self.countdown -= Duration::min(self.countdown, dt);
-        if !self.countdown.is_zero() {
-            return Some(self);
+    fn tick(&mut self, dt: Duration) -> Transition {
+        self.update(dt);
+        if self.is_over() {
+            let path = Path::new(LAST_MATCH);
+            if let Err(error) = self.recording.save(path) {
+                eprintln!("Failed to save replay {}: {}", path.display(), error);
+            }
+            let end_game = EndGame::new(self);
+            let recording = self.recording.clone();
+            return Transition::Replace(Box::new(end_game.with_recording(recording)));
         }
-        self.apply_movement(dt);
+        Transition::Stay
+    }
 
-        // don't let right paddle to go out of wall limits
-        if self.r_paddle.collides(&self.t_wall) {
-            self.r_paddle.y = self.t_wall.y + self.t_wall.h + NUDGE;
-        } else if self.r_paddle.collides(&self.b_wall) {
-            self.r_paddle.y = self.b_wall.y - self.r_paddle.h - NUDGE;
-        }
+    fn draw(&self, ctx: &mut dyn Renderer, alpha: f32) {
+        ctx.draw_rectangle(&self.prev_ball.lerp(&self.ball, alpha));
+        ctx.draw_rectangle(&self.prev_l_paddle.lerp(&self.l_paddle, alpha));
+        ctx.draw_rectangle(&self.prev_r_paddle.lerp(&self.r_paddle, alpha));
+        ctx.draw_rectangle(&self.t_wall);
+        ctx.draw_rectangle(&self.b_wall);
+        ctx.draw_text(&self.l_score);
+        ctx.draw_text(&self.r_score);
+        ctx.draw_text(&self.status);
+    }
 
-        // don't let left paddle to go out of wall limits
-        if self.l_paddle.collides(&self.t_wall) {
-            self.l_paddle.y = self.t_wall.y + self.t_wall.h + NUDGE;
-        } else if self.l_paddle.collides(&self.b_wall) {
-            self.l_paddle.y = self.b_wall.y - self.l_paddle.h - NUDGE;
-        }
+    fn action_down(&mut self, action: Action) -> Transition {
+        self.press(action);
+        Transition::Stay
+    }
 
-        // reflect ball Y-movement if it hits the walls.
-        if self.b_wall.collides(&self.ball) {
-            self.ball.y = self.b_wall.y - self.ball.h - NUDGE;
-            self.ball_y_movement = -self.ball_y_movement;
-            self.accelerate_ball();
-        } else if self.t_wall.collides(&self.ball) {
-            self.ball.y = self.t_wall.y + self.t_wall.h + NUDGE;
-            self.ball_y_movement = -self.ball_y_movement;
-            self.accelerate_ball();
-        }
+    fn axis(&mut self, player: Player, value: f32) -> Transition {
+        self.steer(player, value);
+        Transition::Stay
+    }
 
-        // reflect ball X-movement if it hits the paddles.
-        if self.l_paddle.collides(&self.ball) {
-            self.ball.x = self.l_paddle.x + self.l_paddle.w + NUDGE;
-            self.ball_x_movement = -self.ball_x_movement;
-            self.accelerate_ball();
-        } else if self.r_paddle.collides(&self.ball) {
-            self.ball.x = self.r_paddle.x - self.ball.w - NUDGE;
-            self.ball_x_movement = -self.ball_x_movement;
-            self.accelerate_ball();
-        }
+    fn mouse_move(&mut self, _x: f32, y: f32) -> Transition {
+        self.aim(Player::One, y);
+        Transition::Stay
+    }
 
-        // Check whether ball hits the goals.
-        if self.ball.x <= 0.0 {
-            self.clear_state();
-            self.r_points += 1;
-            if self.r_points >= 10 {
-                return Some(Box::new(EndGame::new(self.l_points, self.r_points)));
-            }
-            self.r_score.set_text(self.r_points);
-        } else if (self.ball.x + self.ball.w) >= 1.0 {
-            self.clear_state();
-            self.l_points += 1;
-            if self.l_points >= 10 {
-                return Some(Box::new(EndGame::new(self.l_points, self.r_points)));
+    fn action_up(&mut self, action: Action) -> Transition {
+        if action == Action::Pause {
+            // Stop the paddles as their keys are released while the pause menu is shown.
+            for player in [Player::One, Player::Two] {
+                self.release(Action::MoveUp(player));
+                self.release(Action::MoveDown(player));
             }
-            self.l_score.set_text(self.l_points);
+            return Transition::Push(Box::new(Pause::new(self.settings)));
         }
-        Some(self)
+        self.release(action);
+        Transition::Stay
     }
 
-    fn draw(&self, ctx: &Graphics) {
-        ctx.draw_rectangle(&self.ball);
-        ctx.draw_rectangle(&self.l_paddle);
-        ctx.draw_rectangle(&self.r_paddle);
-        ctx.draw_rectangle(&self.t_wall);
-        ctx.draw_rectangle(&self.b_wall);
-        ctx.draw_text(&self.l_score);
-        ctx.draw_text(&self.r_score);
+    fn take_sounds(&mut self) -> Vec<Sound> {
+        self.take_events()
+            .iter()
+            .filter_map(Sound::of_event)
+            .collect()
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    fn key_down(mut self: Box<Self>, key: u16) -> Option<Box<dyn Scene>> {
-        match VIRTUAL_KEY(key) {
-            VK_UP => self.r_movement = -1.0,
-            VK_DOWN => self.r_movement = 1.0,
-            VK_W => self.l_movement = -1.0,
-            VK_S => self.l_movement = 1.0,
-            _ => (),
+    #[test]
+    fn ball_does_not_pass_the_paddles_with_huge_time_steps() {
+        // The paddles cover the whole height between the walls, so the ball can never score.
+        let physics = Physics {
+            paddle_height: 0.9,
+            ..Physics::default()
+        };
+        let mut court = Court::new(MatchSettings {
+            mode: Mode::TwoPlayers,
+            physics,
+            ..MatchSettings::default()
+        });
+        for _ in 0..50 {
+            court.update(Duration::from_secs(3));
+            assert_eq!(court.points(), (0, 0));
+            assert!(court.ball.x >= court.l_paddle.x + court.l_paddle.w);
+            assert!(court.ball.x + court.ball.w <= court.r_paddle.x);
         }
-        Some(self)
+        assert!(court.steps() == 50 && court.statistics().l_hits > 0);
     }
 
-    fn key_up(mut self: Box<Self>, key: u16) -> Option<Box<dyn Scene>> {
-        match VIRTUAL_KEY(key) {
-            VK_UP => self.r_movement = f32::max(self.r_movement, 0.0),
-            VK_DOWN => self.r_movement = f32::min(self.r_movement, 0.0),
-            VK_W => self.l_movement = f32::max(self.l_movement, 0.0),
-            VK_S => self.l_movement = f32::min(self.l_movement, 0.0),
-            _ => (),
+    #[test]
+    fn paddle_bounce_angle_follows_the_offset_and_keeps_the_speed() {
+        let mut court = Court::new(MatchSettings::default());
+        let max_angle = court.settings.physics.max_bounce_angle;
+        for (side, direction) in [(Side::Left, 1.0), (Side::Right, -1.0)] {
+            let paddle = match side {
+                Side::Left => court.l_paddle,
+                Side::Right => court.r_paddle,
+            };
+            let reach = (paddle.h + court.ball.h) / 2.0;
+            let mut previous = -f32::INFINITY;
+            // The offsets beyond the paddle ends are clamped to the steepest angle.
+            for offset in [-1.5, -1.0, -0.5, -0.1, 0.0, 0.25, 0.75, 1.0, 1.5] {
+                let center = paddle.y + paddle.h / 2.0 + offset * reach;
+                court.ball.y = center - court.ball.h / 2.0;
+                court.ball_x_movement = -direction * 0.0008;
+                court.ball_y_movement = 0.0003;
+                let speed = court.ball_x_movement.hypot(court.ball_y_movement);
+
+                court.bounce_from_paddle(side);
+                let (x, y) = (court.ball_x_movement, court.ball_y_movement);
+                let angle = y.atan2(direction * x);
+                assert_eq!(x.signum(), direction, "{:?} {}", side, offset);
+                assert!((x.hypot(y) - speed).abs() < 1e-7, "{:?} {}", side, offset);
+                let expected = f32::clamp(offset, -1.0, 1.0) * max_angle;
+                assert!((angle - expected).abs() < 1e-4, "{:?} {}", side, offset);
+                assert!(angle.abs() <= max_angle + 1e-6, "{:?} {}", side, offset);
+                assert!(angle >= previous, "{:?} {}", side, offset);
+                previous = angle;
+            }
         }
-        Some(self)
+    }
+
+    #[test]
+    fn paddle_taller_than_the_court_stays_at_the_top_wall() {
+        let physics = Physics {
+            paddle_height: 1.2,
+            ..Physics::default()
+        };
+        let mut court = Court::new(MatchSettings {
+            mode: Mode::TwoPlayers,
+            physics,
+            ..MatchSettings::default()
+        });
+        court.steer(Player::One, 1.0);
+        for _ in 0..100 {
+            court.update(Duration::from_millis(10));
+        }
+        assert_eq!(
+            court.l_paddle.y,
+            court.t_wall.y + court.t_wall.h + physics.nudge
+        );
+    }
+
+    #[test]
+    fn synced_snapshot_is_not_guessed_as_a_goal() {
+        let settings = MatchSettings::default();
+        let mut snapshot = Court::new(settings).snapshot();
+        snapshot.l_points = 3;
+
+        let mut court = Court::new(settings);
+        court.sync(&snapshot);
+        assert!(court.take_events().is_empty());
+        assert_eq!(court.points(), (3, 0));
+        assert_eq!(court.statistics().l_goals, 0);
+        assert!(!court.statistics().approximate);
+
+        snapshot.l_points = 4;
+        court.restore(&snapshot);
+        assert!(court.take_events() == [GameEvent::Goal { scorer: Side::Left }]);
+        assert!(court.statistics().approximate);
     }
 }
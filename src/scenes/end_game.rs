@@ -1,69 +1,239 @@
 use std::time::Duration;
 
-use windows::Win32::UI::Input::KeyboardAndMouse::{VIRTUAL_KEY, VK_RETURN};
-
 use crate::{
+    audio::Sound,
+    bindings::Bindings,
+    context::Context,
     geometry::{Text, TextSize},
-    graphics::Graphics,
-    scenes::MainMenu,
-    scenes::Scene,
+    input::{Action, Key},
+    recording::Recording,
+    renderer::Renderer,
+    rules::clock,
+    scenes::{Court, MainMenu, Mode, Replay, Scene, Transition},
 };
 
+/// The key which starts the replay of the match.
+const REPLAY_KEY: Key = Key::R;
+
+/// The vertical position of the header row of the statistics table.
+const TABLE_Y: f32 = 0.41;
+
+/// The vertical space between the rows of the statistics table.
+const ROW_SPACING: f32 = 0.045;
+
+/// The horizontal positions of the label, left player and right player columns of the table.
+const COLUMNS_X: [f32; 3] = [0.36, 0.62, 0.76];
+
 /// The scene which shows the end game results.
 pub struct EndGame {
     topic: Text,
     result: Text,
+    /// The cells of the statistics table row by row.
+    table: Vec<Text>,
+    difficulty: Option<Text>,
+    rules: Text,
     help: Text,
+    bindings: Bindings,
+    recording: Option<Recording>,
+    /// The sounds of the scene which have not been played yet.
+    sounds: Vec<Sound>,
 }
 
 impl EndGame {
-    pub fn new(l_score: u8, r_score: u8) -> Self {
-        Self {
+    /// Build the results of the match which was played on the given court.
+    ///
+    /// The result shows the won sets instead of the points when the match is played in sets. The
+    /// statistics are marked approximate when the court has guessed them from received states.
+    pub fn new(court: &Court) -> Self {
+        let settings = court.settings();
+        let (l_score, r_score) = match settings.rules.sets {
+            1 => court.points(),
+            _ => court.sets(),
+        };
+        let statistics = court.statistics();
+        let (l_share, r_share) = statistics.serves_won();
+        let speed = statistics.top_speed / settings.physics.ball_velocity;
+        let rows = [
+            [
+                if statistics.approximate {
+                    "APPROXIMATE"
+                } else {
+                    ""
+                },
+                "LEFT",
+                "RIGHT",
+            ]
+            .map(String::from),
+            [
+                "HITS".to_string(),
+                statistics.l_hits.to_string(),
+                statistics.r_hits.to_string(),
+            ],
+            [
+                "SERVES WON".to_string(),
+                format!("{:.0}%", l_share * 100.0),
+                format!("{:.0}%", r_share * 100.0),
+            ],
+            [
+                "LONGEST RALLY".to_string(),
+                format!("{} HITS", statistics.longest_rally()),
+                String::new(),
+            ],
+            [
+                "AVERAGE RALLY".to_string(),
+                format!("{:.1} HITS", statistics.average_rally()),
+                String::new(),
+            ],
+            [
+                "TOP SPEED".to_string(),
+                format!("{:.1}X SERVE", speed),
+                String::new(),
+            ],
+            [
+                "DURATION".to_string(),
+                clock(statistics.duration),
+                String::new(),
+            ],
+        ];
+        let mut end_game = Self {
             topic: Text {
                 x: 0.5,
-                y: 0.25,
+                y: 0.11,
                 text: "GAME OVER".encode_utf16().collect(),
                 size: TextSize::Big,
             },
             result: Text {
                 x: 0.5,
-                y: 0.525,
-                text: format!("{} - {}", r_score, l_score)
+                y: 0.29,
+                text: format!("{} - {}", l_score, r_score)
                     .encode_utf16()
                     .collect(),
                 size: TextSize::Big,
             },
-            help: Text {
+            table: table(rows),
+            difficulty: match settings.mode {
+                Mode::TwoPlayers => None,
+                Mode::Demo | Mode::OnePlayer => Some(Text {
+                    x: 0.5,
+                    y: 0.74,
+                    text: format!("DIFFICULTY: {}", settings.difficulty.label())
+                        .encode_utf16()
+                        .collect(),
+                    size: TextSize::Small,
+                }),
+            },
+            rules: Text {
                 x: 0.5,
-                y: 0.75,
-                text: "Press ENTER to go back to main menu"
+                y: 0.8,
+                text: format!("RULES: {}", settings.rules.describe())
                     .encode_utf16()
                     .collect(),
+                size: TextSize::Tiny,
+            },
+            help: Text {
+                x: 0.5,
+                y: 0.88,
+                text: Vec::new(),
                 size: TextSize::Small,
             },
-        }
+            bindings: Bindings::default(),
+            recording: None,
+            sounds: vec![Sound::GameOver],
+        };
+        end_game.refresh_help();
+        end_game
+    }
+
+    /// Let the players watch the replay of the match from the given recording.
+    pub fn with_recording(mut self, recording: Recording) -> Self {
+        self.recording = Some(recording);
+        self.refresh_help();
+        self
+    }
+
+    /// Update the instructions to name the keys which leave the scene.
+    fn refresh_help(&mut self) {
+        let confirm = self.bindings.key(Action::Confirm).label();
+        self.help.set_text(match self.recording {
+            Some(_) => format!(
+                "Press {} to go back to main menu or {} to watch the replay",
+                confirm,
+                REPLAY_KEY.label()
+            ),
+            None => format!("Press {} to go back to main menu", confirm),
+        });
     }
 }
 
 impl Scene for EndGame {
-    fn tick(self: Box<Self>, _dt: Duration) -> Option<Box<dyn Scene>> {
-        Some(self)
+    fn tick(&mut self, _dt: Duration) -> Transition {
+        Transition::Stay
     }
 
-    fn draw(&self, ctx: &Graphics) {
+    fn draw(&self, ctx: &mut dyn Renderer, _alpha: f32) {
         ctx.draw_text(&self.topic);
         ctx.draw_text(&self.result);
+        for cell in &self.table {
+            ctx.draw_text(cell);
+        }
+        if let Some(difficulty) = self.difficulty.as_ref() {
+            ctx.draw_text(difficulty);
+        }
+        ctx.draw_text(&self.rules);
         ctx.draw_text(&self.help);
     }
 
-    fn key_down(self: Box<Self>, _key: u16) -> Option<Box<dyn Scene>> {
-        Some(self)
+    fn action_down(&mut self, _action: Action) -> Transition {
+        Transition::Stay
+    }
+
+    fn action_up(&mut self, action: Action) -> Transition {
+        match action {
+            Action::Confirm => Transition::Replace(Box::new(MainMenu::new())),
+            _ => Transition::Stay,
+        }
+    }
+
+    fn enter(&mut self, context: &Context) {
+        self.bindings = context.options.bindings;
+        self.refresh_help();
     }
 
-    fn key_up(self: Box<Self>, key: u16) -> Option<Box<dyn Scene>> {
-        if VIRTUAL_KEY(key) == VK_RETURN {
-            return Some(Box::new(MainMenu::new()));
+    fn capture_key(&mut self, key: Key) -> Option<Transition> {
+        // The menu actions take precedence in case they are bound to the replay key.
+        if key != REPLAY_KEY || self.bindings.actions(key).any(|action| action.is_menu()) {
+            return None;
+        }
+        let recording = self.recording.take()?;
+        Some(Transition::Replace(Box::new(Replay::new(recording))))
+    }
+
+    fn take_sounds(&mut self) -> Vec<Sound> {
+        std::mem::take(&mut self.sounds)
+    }
+}
+
+/// Build the cells of the statistics table from the given rows of label, left and right columns.
+///
+/// A value without a right column is a statistic of the whole match, which spans both columns.
+fn table<const N: usize>(rows: [[String; 3]; N]) -> Vec<Text> {
+    let mut cells = Vec::new();
+    for (i, [label, left, right]) in rows.into_iter().enumerate() {
+        let y = TABLE_Y + i as f32 * ROW_SPACING;
+        let mut columns = vec![(COLUMNS_X[0], label)];
+        if right.is_empty() {
+            columns.push(((COLUMNS_X[1] + COLUMNS_X[2]) / 2.0, left));
+        } else {
+            columns.extend([(COLUMNS_X[1], left), (COLUMNS_X[2], right)]);
+        }
+        for (x, text) in columns {
+            cells.push(Text {
+                x,
+                y,
+                text: text.encode_utf16().collect(),
+                size: TextSize::Tiny,
+            });
         }
-        Some(self)
     }
+    cells
 }
@@ -0,0 +1,193 @@
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use crate::{
+    context::Context,
+    geometry::{Text, TextSize},
+    input::{Action, Key},
+    network::{DEFAULT_PORT, SPECTATOR_PORT},
+    options::Options,
+    renderer::Renderer,
+    scenes::menu::Menu,
+    scenes::{Lobby, MainMenu, Scene, Transition},
+};
+
+/// The length of the longest IPv4 address e.g. `255.255.255.255`.
+const MAX_ADDRESS_LENGTH: usize = 15;
+
+/// The selectable ways to join the match of the host.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Item {
+    Play,
+    Watch,
+}
+
+/// The items of the menu in the order they are shown.
+const ITEMS: [Item; 2] = [Item::Play, Item::Watch];
+
+/// The scene where the player types the address of the host to join as a player or a spectator.
+pub struct JoinMenu {
+    topic: Text,
+    address: Text,
+    help: Text,
+    menu: Menu,
+    /// The options where the joined address is saved.
+    options: Options,
+    /// The address typed so far.
+    input: String,
+}
+
+impl JoinMenu {
+    /// Build the menu with the given address of the previously joined host typed in.
+    pub fn new(address: Ipv4Addr) -> Self {
+        let mut menu = Self {
+            topic: Text {
+                x: 0.5,
+                y: 0.25,
+                text: "JOIN GAME".encode_utf16().collect(),
+                size: TextSize::Big,
+            },
+            address: Text {
+                x: 0.5,
+                y: 0.5,
+                text: Vec::new(),
+                size: TextSize::Medium,
+            },
+            help: Text {
+                x: 0.5,
+                y: 0.62,
+                text: Vec::new(),
+                size: TextSize::Tiny,
+            },
+            menu: Menu::new(0.72, 0.08, ITEMS.map(|_| TextSize::Small)),
+            options: Options::default(),
+            input: address.to_string(),
+        };
+        for (i, item) in ITEMS.iter().enumerate() {
+            match item {
+                Item::Play => menu.menu.set_label(i, "PLAY"),
+                Item::Watch => menu.menu.set_label(i, "WATCH"),
+            }
+        }
+        menu.refresh_address();
+        menu.show_help();
+        menu
+    }
+
+    /// Update the shown address to match the typed address.
+    fn refresh_address(&mut self) {
+        self.address.set_text(format!("{}_", self.input));
+    }
+
+    /// Show the instructions of how to use the menu.
+    fn show_help(&mut self) {
+        self.help.set_text(format!(
+            "Type the address of the host and press {} or {} to go back.",
+            self.options.bindings.key(Action::Confirm).label(),
+            self.options.bindings.key(Action::Back).label()
+        ));
+    }
+
+    /// Remember the typed address and start joining the host at it in the selected way.
+    fn join(&mut self) -> Transition {
+        let address: Ipv4Addr = match self.input.parse() {
+            Ok(address) => address,
+            Err(_) => {
+                self.help
+                    .set_text(format!("{} is not a valid address.", self.input));
+                return Transition::Stay;
+            }
+        };
+        let options = Options {
+            join_address: address,
+            ..self.options
+        };
+        let lobby = match ITEMS[self.menu.selection()] {
+            Item::Play => Lobby::join(SocketAddr::from((address, DEFAULT_PORT))),
+            Item::Watch => Lobby::watch(SocketAddr::from((address, SPECTATOR_PORT))),
+        };
+        let lobby = Transition::Replace(Box::new(lobby));
+        Transition::Save(Box::new(options), Box::new(lobby))
+    }
+}
+
+impl Scene for JoinMenu {
+    fn tick(&mut self, _dt: Duration) -> Transition {
+        Transition::Stay
+    }
+
+    fn draw(&self, ctx: &mut dyn Renderer, _alpha: f32) {
+        ctx.draw_text(&self.topic);
+        ctx.draw_text(&self.address);
+        ctx.draw_text(&self.help);
+        self.menu.draw(ctx);
+    }
+
+    fn action_down(&mut self, _action: Action) -> Transition {
+        Transition::Stay
+    }
+
+    fn action_up(&mut self, action: Action) -> Transition {
+        match action {
+            Action::MenuUp => {
+                self.menu.move_selection(-1);
+                Transition::Stay
+            }
+            Action::MenuDown => {
+                self.menu.move_selection(1);
+                Transition::Stay
+            }
+            Action::Confirm => self.join(),
+            Action::Back => Transition::Replace(Box::new(MainMenu::new())),
+            _ => Transition::Stay,
+        }
+    }
+
+    fn mouse_move(&mut self, x: f32, y: f32) -> Transition {
+        self.menu.hover(x, y);
+        Transition::Stay
+    }
+
+    fn mouse_click(&mut self, x: f32, y: f32) -> Transition {
+        if self.menu.hover(x, y) {
+            self.join()
+        } else {
+            Transition::Stay
+        }
+    }
+
+    fn enter(&mut self, context: &Context) {
+        self.options = context.options;
+        self.show_help();
+    }
+
+    fn capture_key(&mut self, key: Key) -> Option<Transition> {
+        match key {
+            Key::Backspace => {
+                self.input.pop();
+            }
+            Key::Period
+            | Key::Num0
+            | Key::Num1
+            | Key::Num2
+            | Key::Num3
+            | Key::Num4
+            | Key::Num5
+            | Key::Num6
+            | Key::Num7
+            | Key::Num8
+            | Key::Num9 => {
+                if self.input.len() < MAX_ADDRESS_LENGTH {
+                    self.input.push(match key {
+                        Key::Period => '.',
+                        _ => key.label().chars().next()?,
+                    });
+                }
+            }
+            _ => return None,
+        }
+        self.show_help();
+        self.refresh_address();
+        Some(Transition::Stay)
+    }
+}
@@ -0,0 +1,248 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crate::{
+    bindings::Bindings,
+    context::Context,
+    geometry::{Text, TextSize},
+    input::Action,
+    network::{local_address, Connection, Listener, Message, Netcode, DEFAULT_PORT},
+    renderer::Renderer,
+    scenes::{MainMenu, MatchSettings, NetworkMatch, RollbackMatch, Scene, Spectate, Transition},
+};
+
+/// The interval of the hello messages which are sent until the host accepts the client.
+const HELLO_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The time after which the client stops waiting for the host.
+const JOIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The progress of setting up a network match.
+enum State {
+    /// The host waits for a client to join the match with the given settings and netcode.
+    Hosting(Listener, MatchSettings, Netcode),
+    /// The client or the spectator waits for the host to accept it.
+    Joining {
+        connection: Connection,
+        spectate: bool,
+        waited: Duration,
+        since_hello: Duration,
+    },
+    /// The network match could not be set up or it was interrupted.
+    Failed,
+}
+
+/// The scene where the players wait for each other before a network match.
+pub struct Lobby {
+    topic: Text,
+    status: Text,
+    help: Text,
+    bindings: Bindings,
+    state: State,
+}
+
+impl Lobby {
+    /// Start waiting for a client to join a match with the given settings and netcode.
+    pub fn host(settings: MatchSettings, netcode: Netcode) -> Self {
+        match Listener::bind(DEFAULT_PORT) {
+            Ok(listener) => {
+                let address = match local_address() {
+                    Some(address) => format!("{}:{}", address, DEFAULT_PORT),
+                    None => format!("PORT {}", DEFAULT_PORT),
+                };
+                let status = format!("WAITING FOR A PLAYER AT {}", address);
+                let state = State::Hosting(listener, settings, netcode);
+                Self::new("HOST GAME", status, state)
+            }
+            Err(error) => Self::failed(format!("CANNOT HOST A GAME: {}", error)),
+        }
+    }
+
+    /// Start asking the host at the given address to accept this client.
+    pub fn join(address: SocketAddr) -> Self {
+        Self::connect(address, false)
+    }
+
+    /// Start asking the host at the given address to accept this spectator.
+    pub fn watch(address: SocketAddr) -> Self {
+        Self::connect(address, true)
+    }
+
+    fn connect(address: SocketAddr, spectate: bool) -> Self {
+        match Connection::connect(address) {
+            Ok(connection) => {
+                let status = format!("JOINING {}", address);
+                let state = State::Joining {
+                    connection,
+                    spectate,
+                    waited: Duration::ZERO,
+                    // The first hello is sent immediately.
+                    since_hello: HELLO_INTERVAL,
+                };
+                let topic = if spectate { "WATCH GAME" } else { "JOIN GAME" };
+                Self::new(topic, status, state)
+            }
+            Err(error) => Self::failed(format!("CANNOT JOIN A GAME: {}", error)),
+        }
+    }
+
+    /// Show that a network match could not be set up or it was interrupted.
+    pub fn failed<T: ToString>(message: T) -> Self {
+        Self::new("OFFLINE", message, State::Failed)
+    }
+
+    fn new<T: ToString>(topic: &str, status: T, state: State) -> Self {
+        let mut lobby = Self {
+            topic: Text {
+                x: 0.5,
+                y: 0.25,
+                text: topic.encode_utf16().collect(),
+                size: TextSize::Big,
+            },
+            status: Text {
+                x: 0.5,
+                y: 0.5,
+                text: Vec::new(),
+                size: TextSize::Small,
+            },
+            help: Text {
+                x: 0.5,
+                y: 0.62,
+                text: Vec::new(),
+                size: TextSize::Tiny,
+            },
+            bindings: Bindings::default(),
+            state,
+        };
+        lobby.status.set_text(status.to_string().to_uppercase());
+        lobby.refresh_help();
+        lobby
+    }
+
+    /// Update the instructions to name the keys which are bound to the actions of the lobby.
+    fn refresh_help(&mut self) {
+        self.help.set_text(match self.state {
+            State::Failed => format!(
+                "Press {} to go back to main menu",
+                self.bindings.key(Action::Confirm).label()
+            ),
+            _ => format!(
+                "Press {} to cancel",
+                self.bindings.key(Action::Back).label()
+            ),
+        });
+    }
+
+    /// Stop waiting and show the given reason.
+    fn fail<T: ToString>(&mut self, message: T) {
+        let bindings = self.bindings;
+        *self = Self::failed(message);
+        self.bindings = bindings;
+        self.refresh_help();
+    }
+}
+
+impl Scene for Lobby {
+    fn tick(&mut self, dt: Duration) -> Transition {
+        match &mut self.state {
+            State::Hosting(listener, settings, netcode) => {
+                match listener.accept(settings, *netcode) {
+                    Ok(Some(connection)) => {
+                        let settings = *settings;
+                        let scene: Box<dyn Scene> = match netcode {
+                            Netcode::Authoritative => {
+                                Box::new(NetworkMatch::host(connection, settings))
+                            }
+                            Netcode::Rollback => {
+                                Box::new(RollbackMatch::host(connection, settings))
+                            }
+                        };
+                        return Transition::Replace(scene);
+                    }
+                    Ok(None) => (),
+                    Err(error) => self.fail(format!("CANNOT HOST A GAME: {}", error)),
+                }
+            }
+            State::Joining {
+                connection,
+                waited,
+                since_hello,
+                ..
+            } => {
+                *waited += dt;
+                *since_hello += dt;
+                if *since_hello >= HELLO_INTERVAL {
+                    *since_hello = Duration::ZERO;
+                    if let Err(error) = connection.send(&Message::Hello) {
+                        self.fail(format!("CANNOT JOIN A GAME: {}", error));
+                        return Transition::Stay;
+                    }
+                }
+                let messages = match connection.receive() {
+                    Ok(messages) => messages,
+                    Err(error) => {
+                        self.fail(format!("CANNOT JOIN A GAME: {}", error));
+                        return Transition::Stay;
+                    }
+                };
+                for message in messages {
+                    if let Message::Welcome(settings, netcode) = message {
+                        let state = std::mem::replace(&mut self.state, State::Failed);
+                        let scene: Box<dyn Scene> = match state {
+                            State::Joining {
+                                connection,
+                                spectate: true,
+                                ..
+                            } => Box::new(Spectate::new(connection, settings)),
+                            State::Joining { connection, .. } => match netcode {
+                                Netcode::Authoritative => {
+                                    Box::new(NetworkMatch::client(connection, settings))
+                                }
+                                Netcode::Rollback => {
+                                    Box::new(RollbackMatch::client(connection, settings))
+                                }
+                            },
+                            _ => unreachable!(),
+                        };
+                        return Transition::Replace(scene);
+                    }
+                }
+                if *waited >= JOIN_TIMEOUT {
+                    let message = format!("NO HOST FOUND AT {}", connection.peer());
+                    self.fail(message);
+                }
+            }
+            State::Failed => (),
+        }
+        Transition::Stay
+    }
+
+    fn draw(&self, ctx: &mut dyn Renderer, _alpha: f32) {
+        ctx.draw_text(&self.topic);
+        ctx.draw_text(&self.status);
+        ctx.draw_text(&self.help);
+    }
+
+    fn action_down(&mut self, _action: Action) -> Transition {
+        Transition::Stay
+    }
+
+    fn action_up(&mut self, action: Action) -> Transition {
+        match (action, &mut self.state) {
+            (Action::Back, State::Joining { connection, .. }) => {
+                // The host may have accepted the client already, so tell it to stop waiting.
+                let _ = connection.send(&Message::Bye);
+                Transition::Replace(Box::new(MainMenu::new()))
+            }
+            (Action::Back, _) | (Action::Confirm, State::Failed) => {
+                Transition::Replace(Box::new(MainMenu::new()))
+            }
+            _ => Transition::Stay,
+        }
+    }
+
+    fn enter(&mut self, context: &Context) {
+        self.bindings = context.options.bindings;
+        self.refresh_help();
+    }
+}
@@ -1,28 +1,58 @@
-use std::time::Duration;
-
-use windows::Win32::UI::Input::KeyboardAndMouse::{VIRTUAL_KEY, VK_DOWN, VK_RETURN, VK_UP};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::{
+    audio::Sound,
+    context::Context,
     geometry::{Rectangle, Text, TextSize},
-    graphics::Graphics,
-    scenes::Court,
-    scenes::Scene,
+    input::Action,
+    renderer::Renderer,
+    scenes::menu::Menu,
+    scenes::{Court, JoinMenu, Lobby, Mode, OptionsMenu, Scene, Transition},
 };
 
+/// The vertical position of the first menu item.
+const ITEMS_Y: f32 = 0.46;
+
+/// The vertical space between the menu items.
+const ITEMS_SPACING: f32 = 0.07;
+
+/// The selectable items of the menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Item {
+    OnePlayer,
+    TwoPlayers,
+    HostGame,
+    JoinGame,
+    Options,
+    Quit,
+}
+
+/// The items of the menu in the order they are shown.
+const ITEMS: [Item; 6] = [
+    Item::OnePlayer,
+    Item::TwoPlayers,
+    Item::HostGame,
+    Item::JoinGame,
+    Item::Options,
+    Item::Quit,
+];
+
 /// The menu where player can select to start or quit the game.
 pub struct MainMenu {
     header: Rectangle,
     topic: Text,
     help: Text,
-    start: Text,
-    quit: Text,
-    highlighter: Rectangle,
+    menu: Menu,
     footer: Rectangle,
+    /// The options and the tunables which the started matches are played with.
+    context: Context,
+    /// The sounds of the menu which have not been played yet.
+    sounds: Vec<Sound>,
 }
 
 impl MainMenu {
     pub fn new() -> Self {
-        Self {
+        let mut menu = Self {
             header: Rectangle {
                 x: 0.0,
                 y: 0.0,
@@ -38,79 +68,140 @@ impl MainMenu {
             help: Text {
                 x: 0.5,
                 y: 0.4,
-                text: "Select a menu item with UP or DOWN arrows and press ENTER."
-                    .encode_utf16()
-                    .collect(),
+                text: Vec::new(),
                 size: TextSize::Tiny,
             },
-            start: Text {
-                x: 0.5,
-                y: 0.525,
-                text: "START".encode_utf16().collect(),
-                size: TextSize::Medium,
-            },
-            quit: Text {
-                x: 0.5,
-                y: 0.7,
-                text: "QUIT".encode_utf16().collect(),
-                size: TextSize::Medium,
-            },
-            highlighter: Rectangle {
-                x: 0.3,
-                y: 0.51,
-                w: 0.03,
-                h: 0.03,
-            },
+            menu: Menu::new(ITEMS_Y, ITEMS_SPACING, ITEMS.map(|_| TextSize::Medium)),
             footer: Rectangle {
                 x: 0.0,
                 y: 1.0 - 0.03,
                 w: 1.0,
                 h: 0.03,
             },
-        }
+            context: Context::default(),
+            sounds: Vec::new(),
+        };
+        menu.refresh_labels();
+        menu.refresh_help();
+        menu
     }
 
-    /// Change the menu selection based on the current selection.
-    fn toggle_selection(&mut self) {
-        if self.highlighter.y < self.start.y {
-            self.highlighter.y = 0.685;
-        } else {
-            self.highlighter.y = 0.51;
+    /// Update the instructions to name the keys which are bound to the menu actions.
+    fn refresh_help(&mut self) {
+        let bindings = &self.context.options.bindings;
+        self.help.set_text(format!(
+            "Select with {} or {} and press {}.",
+            bindings.key(Action::MenuUp).label(),
+            bindings.key(Action::MenuDown).label(),
+            bindings.key(Action::Confirm).label()
+        ));
+    }
+
+    /// Update the texts of the menu items.
+    fn refresh_labels(&mut self) {
+        for (i, item) in ITEMS.iter().enumerate() {
+            match item {
+                Item::OnePlayer => self.menu.set_label(i, "1 PLAYER"),
+                Item::TwoPlayers => self.menu.set_label(i, "2 PLAYERS"),
+                Item::HostGame => self.menu.set_label(i, "HOST GAME"),
+                Item::JoinGame => self.menu.set_label(i, "JOIN GAME"),
+                Item::Options => self.menu.set_label(i, "OPTIONS"),
+                Item::Quit => self.menu.set_label(i, "QUIT"),
+            }
         }
     }
 }
 
+impl Default for MainMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Scene for MainMenu {
-    fn tick(self: Box<Self>, _dt: Duration) -> Option<Box<dyn Scene>> {
-        Some(self)
+    fn tick(&mut self, _dt: Duration) -> Transition {
+        Transition::Stay
     }
 
-    fn draw(&self, ctx: &Graphics) {
+    fn draw(&self, ctx: &mut dyn Renderer, _alpha: f32) {
         ctx.draw_rectangle(&self.header);
         ctx.draw_text(&self.topic);
         ctx.draw_text(&self.help);
-        ctx.draw_text(&self.start);
-        ctx.draw_text(&self.quit);
-        ctx.draw_rectangle(&self.highlighter);
+        self.menu.draw(ctx);
         ctx.draw_rectangle(&self.footer);
     }
 
-    fn key_down(self: Box<Self>, _key: u16) -> Option<Box<dyn Scene>> {
-        Some(self)
+    fn action_down(&mut self, _action: Action) -> Transition {
+        Transition::Stay
     }
 
-    fn key_up(mut self: Box<Self>, key: u16) -> Option<Box<dyn Scene>> {
-        match VIRTUAL_KEY(key) {
-            VK_UP | VK_DOWN => self.toggle_selection(),
-            VK_RETURN => {
-                if self.highlighter.y < self.start.y {
-                    return Some(Box::new(Court::new()));
-                } else {
-                    return None;
-                }
+    fn action_up(&mut self, action: Action) -> Transition {
+        match action {
+            Action::MenuUp => {
+                self.menu.move_selection(-1);
+                self.sounds.push(Sound::MenuMove);
+            }
+            Action::MenuDown => {
+                self.menu.move_selection(1);
+                self.sounds.push(Sound::MenuMove);
+            }
+            Action::Confirm => {
+                self.sounds.push(Sound::MenuSelect);
+                let mode = match ITEMS[self.menu.selection()] {
+                    Item::OnePlayer => Mode::OnePlayer,
+                    Item::TwoPlayers | Item::HostGame => Mode::TwoPlayers,
+                    Item::JoinGame => {
+                        let join_menu = JoinMenu::new(self.context.options.join_address);
+                        return Transition::Replace(Box::new(join_menu));
+                    }
+                    Item::Options => return Transition::Push(Box::new(OptionsMenu::new())),
+                    Item::Quit => return Transition::Quit,
+                };
+                let Context { options, tuning } = &self.context;
+                let settings = options.match_settings(mode, time_seed(), &tuning.physics);
+                return match ITEMS[self.menu.selection()] {
+                    Item::HostGame => {
+                        Transition::Replace(Box::new(Lobby::host(settings, options.netcode)))
+                    }
+                    _ => Transition::Replace(Box::new(Court::new(settings))),
+                };
             }
             _ => (),
         }
-        Some(self)
+        Transition::Stay
     }
+
+    fn mouse_move(&mut self, x: f32, y: f32) -> Transition {
+        let selection = self.menu.selection();
+        self.menu.hover(x, y);
+        if self.menu.selection() != selection {
+            self.sounds.push(Sound::MenuMove);
+        }
+        Transition::Stay
+    }
+
+    fn mouse_click(&mut self, x: f32, y: f32) -> Transition {
+        if self.menu.hover(x, y) {
+            self.action_up(Action::Confirm)
+        } else {
+            Transition::Stay
+        }
+    }
+
+    fn enter(&mut self, context: &Context) {
+        self.context = *context;
+        self.refresh_help();
+    }
+
+    fn take_sounds(&mut self) -> Vec<Sound> {
+        std::mem::take(&mut self.sounds)
+    }
+}
+
+/// Build a seed for a new match from the current system time.
+fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or_default()
 }
@@ -0,0 +1,88 @@
+use crate::{
+    geometry::{Rectangle, Text, TextSize},
+    renderer::Renderer,
+};
+
+/// The horizontal position of the selection highlighter.
+const HIGHLIGHTER_X: f32 = 0.25;
+
+/// A vertical list of selectable texts with a highlighter next to the selected text.
+pub struct Menu {
+    items: Vec<Text>,
+    spacing: f32,
+    selection: usize,
+    highlighter: Rectangle,
+}
+
+impl Menu {
+    /// Build a new menu with items of the given sizes starting from the given vertical position.
+    pub fn new(y: f32, spacing: f32, sizes: impl IntoIterator<Item = TextSize>) -> Self {
+        Self {
+            items: sizes
+                .into_iter()
+                .enumerate()
+                .map(|(i, size)| Text {
+                    x: 0.5,
+                    y: y + spacing * i as f32,
+                    text: Vec::new(),
+                    size,
+                })
+                .collect(),
+            spacing,
+            selection: 0,
+            highlighter: Rectangle {
+                x: HIGHLIGHTER_X,
+                y: y - 0.015,
+                w: 0.03,
+                h: 0.03,
+            },
+        }
+    }
+
+    /// Get the index of the selected item.
+    pub fn selection(&self) -> usize {
+        self.selection
+    }
+
+    /// Set the text shown for the item with the given index.
+    pub fn set_label<T: ToString>(&mut self, index: usize, label: T) {
+        self.items[index].set_text(label);
+    }
+
+    /// Move the selection by the given amount of items and wrap around the ends.
+    pub fn move_selection(&mut self, amount: isize) {
+        let count = self.items.len() as isize;
+        self.selection = (self.selection as isize + amount).rem_euclid(count) as usize;
+        self.highlighter.y = self.items[self.selection].y - 0.015;
+    }
+
+    /// Select the item at the given position in view coordinates.
+    ///
+    /// Each item covers the row between the highlighter and its mirrored position on the other side.
+    /// Returns a boolean indicating whether there was an item at the position.
+    pub fn hover(&mut self, x: f32, y: f32) -> bool {
+        if !(HIGHLIGHTER_X..=1.0 - HIGHLIGHTER_X).contains(&x) {
+            return false;
+        }
+        let index = self
+            .items
+            .iter()
+            .position(|item| (item.y - y).abs() <= self.spacing / 2.0);
+        match index {
+            Some(index) => {
+                let amount = index as isize - self.selection as isize;
+                self.move_selection(amount);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Render the items and the highlighter with the given rendering context.
+    pub fn draw(&self, ctx: &mut dyn Renderer) {
+        for item in &self.items {
+            ctx.draw_text(item);
+        }
+        ctx.draw_rectangle(&self.highlighter);
+    }
+}
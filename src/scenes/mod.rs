@@ -1,9 +1,28 @@
+mod controls_menu;
 mod court;
 mod end_game;
+mod join_menu;
+mod lobby;
 mod main_menu;
+mod menu;
+mod network_match;
+mod options_menu;
+mod pause;
+mod replay;
+mod rollback_match;
 mod scene;
+mod spectate;
 
-pub use court::Court;
+pub use controls_menu::ControlsMenu;
+pub use court::{Court, MatchSettings, Mode, Physics, Side, Snapshot};
 pub use end_game::EndGame;
+pub use join_menu::JoinMenu;
+pub use lobby::Lobby;
 pub use main_menu::MainMenu;
-pub use scene::Scene;
+pub use network_match::NetworkMatch;
+pub use options_menu::OptionsMenu;
+pub use pause::Pause;
+pub use replay::Replay;
+pub use rollback_match::RollbackMatch;
+pub use scene::{Scene, Transition};
+pub use spectate::{Spectate, DELAY_STEPS};
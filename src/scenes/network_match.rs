@@ -0,0 +1,312 @@
+use std::time::Duration;
+
+use crate::{
+    audio::Sound,
+    geometry::{Text, TextSize},
+    input::{Action, Player},
+    network::{Connection, Message, Netcode, Spectators, SPECTATOR_PORT},
+    renderer::Renderer,
+    scenes::{Court, EndGame, Lobby, MainMenu, MatchSettings, Scene, Transition},
+    timer::STEP,
+};
+
+/// The amount of times the final state is sent as some of the packets may be lost.
+const FINAL_STATES: usize = 5;
+
+/// The movement which the local player requests for their paddle in a network match.
+///
+/// Both players of the local keyboard and the first gamepad control the local paddle.
+#[derive(Default)]
+pub(super) struct LocalPaddle {
+    /// The movement which the local player requests with the keys.
+    up: bool,
+    down: bool,
+    /// The movement which the local player requests with an analog axis.
+    axis: f32,
+    /// The vertical position which the local paddle follows with a pointer.
+    aim: Option<f32>,
+}
+
+impl LocalPaddle {
+    /// Start moving the paddle with the given action.
+    pub(super) fn press(&mut self, action: Action) {
+        match action {
+            Action::MoveUp(_) => self.up = true,
+            Action::MoveDown(_) => self.down = true,
+            _ => return,
+        }
+        self.aim = None;
+    }
+
+    /// Stop moving the paddle with the given action.
+    pub(super) fn release(&mut self, action: Action) {
+        match action {
+            Action::MoveUp(_) => self.up = false,
+            Action::MoveDown(_) => self.down = false,
+            _ => (),
+        }
+    }
+
+    /// Move the paddle with the analog axis of the given player.
+    pub(super) fn steer(&mut self, player: Player, value: f32) {
+        if player == Player::One && !value.is_nan() {
+            self.axis = value.clamp(-1.0, 1.0);
+            if self.axis != 0.0 {
+                self.aim = None;
+            }
+        }
+    }
+
+    /// Let the paddle follow the pointer at the given vertical position.
+    pub(super) fn aim(&mut self, y: f32) {
+        if y.is_finite() {
+            self.aim = Some(y);
+        }
+    }
+
+    /// Resolve the movement of the paddle of the given player on the given court in range [-1, 1].
+    ///
+    /// The keys take precedence over the analog axis, which takes precedence over the pointer.
+    pub(super) fn movement(&self, court: &Court, player: Player) -> f32 {
+        if self.up || self.down {
+            return self.down as u8 as f32 - self.up as u8 as f32;
+        }
+        if self.axis != 0.0 {
+            return self.axis;
+        }
+        match self.aim {
+            Some(aim) => {
+                let snapshot = court.snapshot();
+                let physics = &court.settings().physics;
+                let paddle_y = match player {
+                    Player::One => snapshot.l_paddle_y,
+                    Player::Two => snapshot.r_paddle_y,
+                };
+                let center = paddle_y + physics.paddle_height / 2.0;
+                let reach = physics.paddle_velocity * STEP.as_secs_f32() * 1000.0;
+                ((aim - center) / reach).clamp(-1.0, 1.0)
+            }
+            None => 0.0,
+        }
+    }
+}
+
+/// The end of the network match which is run on this machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Role {
+    /// The host simulates the match and controls the left paddle.
+    Host,
+    /// The client shows the state received from the host and controls the right paddle.
+    Client,
+}
+
+/// The scene where a local player competes against a player on another machine.
+///
+/// The host runs the authoritative simulation and sends its state to the client on each step. The
+/// client sends the movement of its paddle to the host and continues the simulation on its own
+/// until the next state arrives, which hides the lost packets. The host also sends its state to the
+/// spectators of the match.
+pub struct NetworkMatch {
+    court: Court,
+    connection: Connection,
+    role: Role,
+    label: Text,
+    spectators: Option<Spectators>,
+    /// The amount of spectators shown in the label.
+    watching: usize,
+    paddle: LocalPaddle,
+    /// Whether the client has received a state from the host.
+    synced: bool,
+}
+
+impl NetworkMatch {
+    /// Start hosting the match with the given settings for the client of the given connection.
+    pub fn host(connection: Connection, settings: MatchSettings) -> Self {
+        let mut network_match = Self::new(connection, settings, Role::Host);
+        // The match can be played without spectators, e.g. when another game uses the port.
+        match Spectators::bind(SPECTATOR_PORT, settings) {
+            Ok(spectators) => network_match.spectators = Some(spectators),
+            Err(error) => eprintln!("Failed to wait for spectators: {}", error),
+        }
+        network_match
+    }
+
+    /// Start playing the match with the given settings of the host of the given connection.
+    pub fn client(connection: Connection, settings: MatchSettings) -> Self {
+        Self::new(connection, settings, Role::Client)
+    }
+
+    fn new(connection: Connection, settings: MatchSettings, role: Role) -> Self {
+        let mut network_match = Self {
+            court: Court::new(settings),
+            connection,
+            role,
+            label: Text {
+                x: 0.5,
+                y: 0.95,
+                text: Vec::new(),
+                size: TextSize::Tiny,
+            },
+            spectators: None,
+            watching: 0,
+            paddle: LocalPaddle::default(),
+            synced: false,
+        };
+        network_match.refresh_label();
+        network_match
+    }
+
+    /// Update the label which shows the other end and the amount of spectators.
+    fn refresh_label(&mut self) {
+        let peer = self.connection.peer();
+        self.label.set_text(match (self.role, self.watching) {
+            (Role::Host, 0) => format!("HOSTING FOR {}", peer),
+            (Role::Host, watching) => format!("HOSTING FOR {} WITH {} WATCHING", peer, watching),
+            (Role::Client, _) => format!("JOINED {}", peer),
+        });
+    }
+
+    /// Resolve the movement of the local paddle in range [-1, 1].
+    fn movement(&self) -> f32 {
+        let player = match self.role {
+            Role::Host => Player::One,
+            Role::Client => Player::Two,
+        };
+        self.paddle.movement(&self.court, player)
+    }
+
+    /// Leave the match and tell the other end about it.
+    fn leave(&mut self) -> Transition {
+        let _ = self.connection.send(&Message::Bye);
+        Transition::Replace(Box::new(MainMenu::new()))
+    }
+
+    fn tick_host(&mut self, dt: Duration, messages: Vec<Message>) -> Transition {
+        for message in messages {
+            match message {
+                Message::Input(movement) => self.court.steer(Player::Two, movement),
+                // The client keeps saying hello until it receives the settings.
+                Message::Hello => {
+                    let welcome = Message::Welcome(*self.court.settings(), Netcode::Authoritative);
+                    if let Err(error) = self.connection.send(&welcome) {
+                        return fail(error);
+                    }
+                }
+                Message::Bye => return fail("THE OTHER PLAYER LEFT THE MATCH"),
+                Message::Welcome(..) | Message::State(_) => (),
+            }
+        }
+        self.court.steer(Player::One, self.movement());
+        let transition = self.court.tick(dt);
+        let state = Message::State(self.court.snapshot());
+        let sends = match transition {
+            Transition::Stay => 1,
+            _ => FINAL_STATES,
+        };
+        for _ in 0..sends {
+            if let Err(error) = self.connection.send(&state) {
+                return fail(error);
+            }
+        }
+        if let Some(spectators) = self.spectators.as_mut() {
+            if let Err(error) = spectators.update() {
+                eprintln!("Failed to receive from spectators: {}", error);
+            }
+            for _ in 0..sends {
+                spectators.broadcast(&state);
+            }
+            if spectators.count() != self.watching {
+                self.watching = spectators.count();
+                self.refresh_label();
+            }
+        }
+        transition
+    }
+
+    fn tick_client(&mut self, dt: Duration, messages: Vec<Message>) -> Transition {
+        let mut state = None;
+        for message in messages {
+            match message {
+                Message::State(snapshot) => state = Some(snapshot),
+                Message::Bye => return fail("THE OTHER PLAYER LEFT THE MATCH"),
+                Message::Hello | Message::Welcome(..) | Message::Input(_) => (),
+            }
+        }
+        let movement = self.movement();
+        if let Err(error) = self.connection.send(&Message::Input(movement)) {
+            return fail(error);
+        }
+        self.court.steer(Player::Two, movement);
+        match state {
+            Some(snapshot) => {
+                // The first state jumps over the steps which were predicted before it arrived.
+                if self.synced {
+                    self.court.restore(&snapshot);
+                } else {
+                    self.court.sync(&snapshot);
+                    self.synced = true;
+                }
+                // Only the host decides when the match is over.
+                if self.court.is_over() {
+                    return Transition::Replace(Box::new(EndGame::new(&self.court)));
+                }
+            }
+            None => self.court.update(dt),
+        }
+        Transition::Stay
+    }
+}
+
+impl Scene for NetworkMatch {
+    fn tick(&mut self, dt: Duration) -> Transition {
+        let messages = match self.connection.receive() {
+            Ok(messages) => messages,
+            Err(error) => return fail(error),
+        };
+        if messages.is_empty() && self.connection.is_lost() {
+            return fail("THE CONNECTION TO THE OTHER PLAYER WAS LOST");
+        }
+        match self.role {
+            Role::Host => self.tick_host(dt, messages),
+            Role::Client => self.tick_client(dt, messages),
+        }
+    }
+
+    fn draw(&self, ctx: &mut dyn Renderer, alpha: f32) {
+        self.court.draw(ctx, alpha);
+        ctx.draw_text(&self.label);
+    }
+
+    fn action_down(&mut self, action: Action) -> Transition {
+        self.paddle.press(action);
+        Transition::Stay
+    }
+
+    fn action_up(&mut self, action: Action) -> Transition {
+        // The match cannot be paused as the other player would have to wait.
+        if action == Action::Pause {
+            return self.leave();
+        }
+        self.paddle.release(action);
+        Transition::Stay
+    }
+
+    fn axis(&mut self, player: Player, value: f32) -> Transition {
+        self.paddle.steer(player, value);
+        Transition::Stay
+    }
+
+    fn mouse_move(&mut self, _x: f32, y: f32) -> Transition {
+        self.paddle.aim(y);
+        Transition::Stay
+    }
+
+    fn take_sounds(&mut self) -> Vec<Sound> {
+        self.court.take_sounds()
+    }
+}
+
+/// Stop the match and show the reason in the lobby.
+pub(super) fn fail<T: ToString>(message: T) -> Transition {
+    Transition::Replace(Box::new(Lobby::failed(message)))
+}
@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use crate::{
+    context::Context,
+    geometry::{Text, TextSize},
+    input::Action,
+    options::{step_percent, Options},
+    renderer::Renderer,
+    scenes::menu::Menu,
+    scenes::{ControlsMenu, Scene, Transition},
+};
+
+/// The selectable items of the menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Item {
+    Rules,
+    BallSpeed,
+    MaxSpeed,
+    PaddleSize,
+    Difficulty,
+    Theme,
+    Netcode,
+    Controls,
+    Back,
+}
+
+/// The items of the menu in the order they are shown.
+const ITEMS: [Item; 9] = [
+    Item::Rules,
+    Item::BallSpeed,
+    Item::MaxSpeed,
+    Item::PaddleSize,
+    Item::Difficulty,
+    Item::Theme,
+    Item::Netcode,
+    Item::Controls,
+    Item::Back,
+];
+
+/// The menu where players can change the options which are saved between the runs.
+pub struct OptionsMenu {
+    topic: Text,
+    help: Text,
+    menu: Menu,
+    options: Options,
+}
+
+impl OptionsMenu {
+    pub fn new() -> Self {
+        let mut menu = Self {
+            topic: Text {
+                x: 0.5,
+                y: 0.15,
+                text: "OPTIONS".encode_utf16().collect(),
+                size: TextSize::Big,
+            },
+            help: Text {
+                x: 0.5,
+                y: 0.28,
+                text: Vec::new(),
+                size: TextSize::Tiny,
+            },
+            menu: Menu::new(0.36, 0.07, ITEMS.iter().map(|_| TextSize::Small)),
+            options: Options::default(),
+        };
+        menu.refresh_labels();
+        menu.refresh_help();
+        menu
+    }
+
+    /// Update the instructions to name the keys which are bound to the menu actions.
+    fn refresh_help(&mut self) {
+        let bindings = &self.options.bindings;
+        self.help.set_text(format!(
+            "Change with {} or {} and go back with {}.",
+            bindings.key(Action::MenuLeft).label(),
+            bindings.key(Action::MenuRight).label(),
+            bindings.key(Action::Back).label()
+        ));
+    }
+
+    /// Update the texts of the menu items to match the current options.
+    fn refresh_labels(&mut self) {
+        let options = &self.options;
+        for (i, item) in ITEMS.iter().enumerate() {
+            let label = match item {
+                Item::Rules => format!("RULES: {}", options.rules.name()),
+                Item::BallSpeed => format!("BALL SPEED: {}%", options.ball_speed),
+                Item::MaxSpeed => format!("MAX SPEED: {}%", options.max_speed),
+                Item::PaddleSize => format!("PADDLE SIZE: {}%", options.paddle_size),
+                Item::Difficulty => format!("DIFFICULTY: {}", options.difficulty.label()),
+                Item::Theme => format!("COLORS: {}", options.theme.label()),
+                Item::Netcode => format!("NETCODE: {}", options.netcode.label()),
+                Item::Controls => "CONTROLS".to_string(),
+                Item::Back => "BACK".to_string(),
+            };
+            self.menu.set_label(i, label);
+        }
+    }
+
+    /// Change the option of the selected item to the next or the previous value and save it.
+    ///
+    /// The application uses the saved colors right away, which previews the selected theme.
+    fn change_option(&mut self, forward: bool) -> Transition {
+        let options = &mut self.options;
+        match ITEMS[self.menu.selection()] {
+            Item::Rules if forward => options.rules = options.rules.next(),
+            Item::Rules => options.rules = options.rules.previous(),
+            Item::BallSpeed => options.ball_speed = step_percent(options.ball_speed, forward),
+            Item::MaxSpeed => options.max_speed = step_percent(options.max_speed, forward),
+            Item::PaddleSize => options.paddle_size = step_percent(options.paddle_size, forward),
+            Item::Difficulty if forward => options.difficulty = options.difficulty.next(),
+            Item::Difficulty => options.difficulty = options.difficulty.previous(),
+            Item::Theme if forward => options.theme = options.theme.next(),
+            Item::Theme => options.theme = options.theme.previous(),
+            Item::Netcode => options.netcode = options.netcode.next(),
+            Item::Controls | Item::Back => return Transition::Stay,
+        }
+        self.refresh_labels();
+        Transition::Save(Box::new(self.options), Box::new(Transition::Stay))
+    }
+}
+
+impl Default for OptionsMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scene for OptionsMenu {
+    fn tick(&mut self, _dt: Duration) -> Transition {
+        Transition::Stay
+    }
+
+    fn draw(&self, ctx: &mut dyn Renderer, _alpha: f32) {
+        ctx.draw_text(&self.topic);
+        ctx.draw_text(&self.help);
+        self.menu.draw(ctx);
+    }
+
+    fn action_down(&mut self, _action: Action) -> Transition {
+        Transition::Stay
+    }
+
+    fn action_up(&mut self, action: Action) -> Transition {
+        match action {
+            Action::MenuUp => self.menu.move_selection(-1),
+            Action::MenuDown => self.menu.move_selection(1),
+            Action::MenuLeft => return self.change_option(false),
+            Action::MenuRight => return self.change_option(true),
+            Action::Confirm => match ITEMS[self.menu.selection()] {
+                Item::Controls => return Transition::Push(Box::new(ControlsMenu::new())),
+                Item::Back => return Transition::Pop,
+                _ => return self.change_option(true),
+            },
+            Action::Back => return Transition::Pop,
+            _ => (),
+        }
+        Transition::Stay
+    }
+
+    fn mouse_move(&mut self, x: f32, y: f32) -> Transition {
+        self.menu.hover(x, y);
+        Transition::Stay
+    }
+
+    fn mouse_click(&mut self, x: f32, y: f32) -> Transition {
+        if self.menu.hover(x, y) {
+            self.action_up(Action::Confirm)
+        } else {
+            Transition::Stay
+        }
+    }
+
+    fn enter(&mut self, context: &Context) {
+        self.options = context.options;
+        self.refresh_labels();
+        self.refresh_help();
+    }
+}
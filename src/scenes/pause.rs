@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use crate::{
+    geometry::{Rectangle, Text, TextSize},
+    input::Action,
+    renderer::Renderer,
+    scenes::menu::Menu,
+    scenes::{Court, MainMenu, MatchSettings, Scene, Transition},
+};
+
+/// The opacity of the shade drawn over the frozen court.
+const SHADE_ALPHA: f32 = 0.6;
+
+/// The selectable items of the menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Item {
+    Resume,
+    Restart,
+    Quit,
+}
+
+/// The items of the menu in the order they are shown.
+const ITEMS: [Item; 3] = [Item::Resume, Item::Restart, Item::Quit];
+
+/// The menu shown on top of a frozen court when the match is paused.
+pub struct Pause {
+    settings: MatchSettings,
+    shade: Rectangle,
+    topic: Text,
+    menu: Menu,
+}
+
+impl Pause {
+    pub fn new(settings: MatchSettings) -> Self {
+        let mut menu = Menu::new(0.6, 0.08, ITEMS.iter().map(|_| TextSize::Small));
+        for (i, item) in ITEMS.iter().enumerate() {
+            match item {
+                Item::Resume => menu.set_label(i, "RESUME"),
+                Item::Restart => menu.set_label(i, "RESTART MATCH"),
+                Item::Quit => menu.set_label(i, "QUIT TO MAIN MENU"),
+            }
+        }
+        Self {
+            settings,
+            shade: Rectangle {
+                x: 0.0,
+                y: 0.0,
+                w: 1.0,
+                h: 1.0,
+            },
+            topic: Text {
+                x: 0.5,
+                y: 0.4,
+                text: "PAUSED".encode_utf16().collect(),
+                size: TextSize::Big,
+            },
+            menu,
+        }
+    }
+}
+
+impl Scene for Pause {
+    fn tick(&mut self, _dt: Duration) -> Transition {
+        Transition::Stay
+    }
+
+    fn draw(&self, ctx: &mut dyn Renderer, _alpha: f32) {
+        let palette = ctx.palette();
+        ctx.set_color(palette.background.with_alpha(SHADE_ALPHA));
+        ctx.draw_rectangle(&self.shade);
+        ctx.set_color(palette.foreground);
+        ctx.draw_text(&self.topic);
+        self.menu.draw(ctx);
+    }
+
+    fn action_down(&mut self, _action: Action) -> Transition {
+        Transition::Stay
+    }
+
+    fn action_up(&mut self, action: Action) -> Transition {
+        match action {
+            Action::MenuUp => self.menu.move_selection(-1),
+            Action::MenuDown => self.menu.move_selection(1),
+            Action::Back | Action::Pause => return Transition::Pop,
+            Action::Confirm => {
+                return match ITEMS[self.menu.selection()] {
+                    Item::Resume => Transition::Pop,
+                    Item::Restart => Transition::Reset(Box::new(Court::new(self.settings))),
+                    Item::Quit => Transition::Reset(Box::new(MainMenu::new())),
+                }
+            }
+            _ => (),
+        }
+        Transition::Stay
+    }
+
+    fn mouse_move(&mut self, x: f32, y: f32) -> Transition {
+        self.menu.hover(x, y);
+        Transition::Stay
+    }
+
+    fn mouse_click(&mut self, x: f32, y: f32) -> Transition {
+        if self.menu.hover(x, y) {
+            self.action_up(Action::Confirm)
+        } else {
+            Transition::Stay
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use crate::{
+    audio::Sound,
+    bindings::Bindings,
+    context::Context,
+    geometry::{Text, TextSize},
+    input::{Action, ActionEvent},
+    recording::Recording,
+    renderer::Renderer,
+    scenes::{Court, EndGame, MainMenu, Scene, Transition},
+};
+
+/// The scene which plays back a recorded match through the same simulation as the live matches.
+pub struct Replay {
+    court: Court,
+    recording: Recording,
+    cursor: usize,
+    label: Text,
+}
+
+impl Replay {
+    pub fn new(recording: Recording) -> Self {
+        let mut replay = Self {
+            court: Court::new(recording.settings),
+            recording,
+            cursor: 0,
+            label: Text {
+                x: 0.5,
+                y: 0.06,
+                text: Vec::new(),
+                size: TextSize::Tiny,
+            },
+        };
+        replay.refresh_label(&Bindings::default());
+        replay
+    }
+
+    /// Get the court where the recorded match is played back.
+    pub fn court(&self) -> &Court {
+        &self.court
+    }
+
+    /// Update the label to name the key which stops the replay.
+    fn refresh_label(&mut self, bindings: &Bindings) {
+        let confirm = bindings.key(Action::Confirm);
+        self.label
+            .set_text(format!("REPLAY - Press {} to stop", confirm.label()));
+    }
+}
+
+impl Scene for Replay {
+    fn tick(&mut self, dt: Duration) -> Transition {
+        // Apply the inputs which were received before the upcoming simulation step.
+        while let Some(input) = self.recording.inputs.get(self.cursor) {
+            if input.step > self.court.steps() {
+                break;
+            }
+            match input.event {
+                ActionEvent::Pressed(action) => self.court.press(action),
+                ActionEvent::Released(action) => self.court.release(action),
+                ActionEvent::Aimed(player, y) => self.court.aim(player, y),
+                ActionEvent::Steered(player, value) => self.court.steer(player, value),
+            }
+            self.cursor += 1;
+        }
+        self.court.update(dt);
+        if self.court.is_over() {
+            let end_game = EndGame::new(&self.court);
+            let recording = self.recording.clone();
+            return Transition::Replace(Box::new(end_game.with_recording(recording)));
+        }
+        Transition::Stay
+    }
+
+    fn draw(&self, ctx: &mut dyn Renderer, alpha: f32) {
+        self.court.draw(ctx, alpha);
+        ctx.draw_text(&self.label);
+    }
+
+    fn action_down(&mut self, _action: Action) -> Transition {
+        Transition::Stay
+    }
+
+    fn action_up(&mut self, action: Action) -> Transition {
+        match action {
+            Action::Confirm | Action::Back => Transition::Replace(Box::new(MainMenu::new())),
+            _ => Transition::Stay,
+        }
+    }
+
+    fn enter(&mut self, context: &Context) {
+        self.refresh_label(&context.options.bindings);
+    }
+
+    fn take_sounds(&mut self) -> Vec<Sound> {
+        self.court.take_sounds()
+    }
+}
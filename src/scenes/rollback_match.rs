@@ -0,0 +1,185 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::{
+    audio::Sound,
+    events::GameEvent,
+    geometry::{Text, TextSize},
+    input::{Action, Player},
+    network::{Connection, Message, Spectators, SPECTATOR_PORT},
+    renderer::Renderer,
+    rollback::{Session, UdpTransport},
+    scenes::network_match::{fail, LocalPaddle},
+    scenes::{EndGame, MainMenu, MatchSettings, Scene, Transition},
+};
+
+/// The amount of times the last inputs and states are sent as some of the packets may be lost.
+const FINAL_PACKETS: usize = 5;
+
+/// The scene where a local player competes against a player on another machine with rollback.
+///
+/// Both ends simulate the match with the inputs of both players and roll back whenever the inputs
+/// of the other player arrive late, so neither end waits for the other to move its own paddle. The
+/// sounds are played once their steps are confirmed, as the predicted steps may still change. The
+/// host also sends the confirmed state to the spectators of the match.
+pub struct RollbackMatch {
+    session: Session<UdpTransport>,
+    local: Player,
+    label: Text,
+    spectators: Option<Spectators>,
+    /// The amount of spectators shown in the label.
+    watching: usize,
+    paddle: LocalPaddle,
+    /// The sounds of the confirmed steps which have not been played yet.
+    sounds: Rc<RefCell<Vec<Sound>>>,
+}
+
+impl RollbackMatch {
+    /// Start hosting the match with the given settings for the client of the given connection.
+    pub fn host(connection: Connection, settings: MatchSettings) -> Self {
+        let transport = UdpTransport::host(connection, settings);
+        let mut rollback_match = Self::new(settings, Player::One, transport);
+        // The match can be played without spectators, e.g. when another game uses the port.
+        match Spectators::bind(SPECTATOR_PORT, settings) {
+            Ok(spectators) => rollback_match.spectators = Some(spectators),
+            Err(error) => eprintln!("Failed to wait for spectators: {}", error),
+        }
+        rollback_match
+    }
+
+    /// Start playing the match with the given settings of the host of the given connection.
+    pub fn client(connection: Connection, settings: MatchSettings) -> Self {
+        Self::new(settings, Player::Two, UdpTransport::client(connection))
+    }
+
+    fn new(settings: MatchSettings, local: Player, transport: UdpTransport) -> Self {
+        let mut session = Session::new(settings, local, transport);
+        let sounds = Rc::new(RefCell::new(Vec::new()));
+        let heard = sounds.clone();
+        session.subscribe(Rc::new(RefCell::new(move |event: &GameEvent| {
+            heard.borrow_mut().extend(Sound::of_event(event))
+        })));
+        let mut rollback_match = Self {
+            session,
+            local,
+            label: Text {
+                x: 0.5,
+                y: 0.95,
+                text: Vec::new(),
+                size: TextSize::Tiny,
+            },
+            spectators: None,
+            watching: 0,
+            paddle: LocalPaddle::default(),
+            sounds,
+        };
+        rollback_match.refresh_label();
+        rollback_match
+    }
+
+    /// Update the label which shows the other end and the amount of spectators.
+    fn refresh_label(&mut self) {
+        let peer = self.session.transport().connection().peer();
+        self.label.set_text(match (self.local, self.watching) {
+            (Player::One, 0) => format!("HOSTING FOR {} WITH ROLLBACK", peer),
+            (Player::One, watching) => format!(
+                "HOSTING FOR {} WITH ROLLBACK AND {} WATCHING",
+                peer, watching
+            ),
+            (Player::Two, _) => format!("JOINED {} WITH ROLLBACK", peer),
+        });
+    }
+
+    /// Send the confirmed state to the spectators the given amount of times.
+    fn update_spectators(&mut self, sends: usize) {
+        let Some(spectators) = self.spectators.as_mut() else {
+            return;
+        };
+        if let Err(error) = spectators.update() {
+            eprintln!("Failed to receive from spectators: {}", error);
+        }
+        let state = Message::State(self.session.confirmed().snapshot());
+        for _ in 0..sends {
+            spectators.broadcast(&state);
+        }
+        if spectators.count() != self.watching {
+            self.watching = spectators.count();
+            self.refresh_label();
+        }
+    }
+
+    /// Deliver the last inputs to the other end and show the confirmed end of the match.
+    fn finish(&mut self) -> Transition {
+        for _ in 0..FINAL_PACKETS {
+            if let Err(error) = self.session.poll() {
+                return fail(error);
+            }
+        }
+        self.update_spectators(FINAL_PACKETS);
+        let mut court = self.session.confirmed().clone();
+        court.set_recording(self.session.recording());
+        Transition::Replace(Box::new(EndGame::new(&court)))
+    }
+
+    /// Leave the match and tell the other end about it.
+    fn leave(&mut self) -> Transition {
+        let _ = self.session.transport_mut().leave();
+        Transition::Replace(Box::new(MainMenu::new()))
+    }
+}
+
+impl Scene for RollbackMatch {
+    fn tick(&mut self, _dt: Duration) -> Transition {
+        // The session always simulates whole steps, so both ends stay in the same state.
+        let movement = self.paddle.movement(self.session.court(), self.local);
+        if let Err(error) = self.session.advance(movement) {
+            return fail(error);
+        }
+        let transport = self.session.transport();
+        if transport.has_left() {
+            return fail("THE OTHER PLAYER LEFT THE MATCH");
+        }
+        if transport.connection().is_lost() {
+            return fail("THE CONNECTION TO THE OTHER PLAYER WAS LOST");
+        }
+        if self.session.confirmed().is_over() {
+            return self.finish();
+        }
+        self.update_spectators(1);
+        Transition::Stay
+    }
+
+    fn draw(&self, ctx: &mut dyn Renderer, alpha: f32) {
+        self.session.court().draw(ctx, alpha);
+        ctx.draw_text(&self.label);
+    }
+
+    fn action_down(&mut self, action: Action) -> Transition {
+        self.paddle.press(action);
+        Transition::Stay
+    }
+
+    fn action_up(&mut self, action: Action) -> Transition {
+        // The match cannot be paused as the other player would have to wait.
+        if action == Action::Pause {
+            return self.leave();
+        }
+        self.paddle.release(action);
+        Transition::Stay
+    }
+
+    fn axis(&mut self, player: Player, value: f32) -> Transition {
+        self.paddle.steer(player, value);
+        Transition::Stay
+    }
+
+    fn mouse_move(&mut self, _x: f32, y: f32) -> Transition {
+        self.paddle.aim(y);
+        Transition::Stay
+    }
+
+    fn take_sounds(&mut self) -> Vec<Sound> {
+        std::mem::take(&mut self.sounds.borrow_mut())
+    }
+}
@@ -1,24 +1,85 @@
 use std::time::Duration;
 
-use crate::graphics::Graphics;
+use crate::{
+    audio::Sound,
+    context::Context,
+    input::{Action, Key, Player},
+    options::Options,
+    renderer::Renderer,
+};
+
+/// A change to the stack of scenes which is requested by the topmost scene.
+pub enum Transition {
+    /// Keep the current scene stack as it is.
+    Stay,
+    /// Put the given scene on top of the current scene.
+    Push(Box<dyn Scene>),
+    /// Remove the current scene and return to the scene below it.
+    Pop,
+    /// Swap the current scene with the given scene.
+    Replace(Box<dyn Scene>),
+    /// Remove all scenes and continue from the given scene.
+    Reset(Box<dyn Scene>),
+    /// Remove all scenes i.e. exit the application.
+    Quit,
+    /// Save the given options into the shared context and then apply the given transition.
+    Save(Box<Options>, Box<Transition>),
+}
 
 /// An application state which handles visible entities and execution logic.
 pub trait Scene {
     /// Update the scene logic and physics simulation with the given time step.
     ///
-    /// Returned value is used as the next scene or application exit (if None).
-    fn tick(self: Box<Self>, dt: Duration) -> Option<Box<dyn Scene>>;
+    /// Only the topmost scene of the stack is being updated.
+    fn tick(&mut self, dt: Duration) -> Transition;
+
+    /// Render the scene contents with the given rendering context.
+    ///
+    /// Alpha is the progress in range [0, 1] from the previous to the current state of the scene.
+    fn draw(&self, ctx: &mut dyn Renderer, alpha: f32);
+
+    /// Tell the scene that an action is being pressed with the key bound to it.
+    fn action_down(&mut self, action: Action) -> Transition;
 
-    /// Render the scene contents on the screen with the given graphics context.
-    fn draw(&self, ctx: &Graphics);
+    /// Tell the scene that an action is being released with the key bound to it.
+    fn action_up(&mut self, action: Action) -> Transition;
 
-    /// Tell the scene that a keyboard key is being pressed.
+    /// Let the scene consume a released keyboard key before it's translated into actions.
     ///
-    /// Returned value is used as the next scene or application exit (if None).
-    fn key_down(self: Box<Self>, key: u16) -> Option<Box<dyn Scene>>;
+    /// Returning `None` lets the key trigger the actions which are bound to it.
+    fn capture_key(&mut self, _key: Key) -> Option<Transition> {
+        None
+    }
 
-    /// Tell the scene that a keyboard key is being released.
+    /// Tell the scene that the analog axis of the given player was moved into the given position.
+    fn axis(&mut self, _player: Player, _value: f32) -> Transition {
+        Transition::Stay
+    }
+
+    /// Tell the scene that the mouse cursor was moved to the given position in view coordinates.
+    fn mouse_move(&mut self, _x: f32, _y: f32) -> Transition {
+        Transition::Stay
+    }
+
+    /// Tell the scene that the left mouse button was clicked at the given position in view
+    /// coordinates.
+    fn mouse_click(&mut self, _x: f32, _y: f32) -> Transition {
+        Transition::Stay
+    }
+
+    /// Tell the scene that it's the topmost scene with the given shared context.
     ///
-    /// Returned value is used as the next scene or application exit (if None).
-    fn key_up(self: Box<Self>, key: u16) -> Option<Box<dyn Scene>>;
+    /// This happens whenever the scene stack changes, which also follows each saved change to the
+    /// context.
+    fn enter(&mut self, _context: &Context) {}
+
+    /// Check whether the scene is drawn on top of the scene below it in the stack.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    /// Take the sounds which the scene has started since the sounds were taken last time.
+    fn take_sounds(&mut self) -> Vec<Sound> {
+        Vec::new()
+    }
 }
@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::{
+    audio::Sound,
+    geometry::{Text, TextSize},
+    input::Action,
+    network::{Connection, Message},
+    renderer::Renderer,
+    scenes::{Court, EndGame, Lobby, MainMenu, MatchSettings, Scene, Snapshot, Transition},
+};
+
+/// The amount of steps the shown state is behind the newest received state.
+///
+/// The delay lets the states which arrive unevenly be shown at an even pace.
+pub const DELAY_STEPS: u32 = 30;
+
+/// The interval of the hello messages which tell the host that the spectator is still watching.
+const HELLO_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The scene where a spectator watches the network match of another machine.
+pub struct Spectate {
+    court: Court,
+    connection: Connection,
+    label: Text,
+    /// The received states in the order of their steps which have not been shown yet.
+    buffer: VecDeque<Snapshot>,
+    /// The step of the match which is currently shown.
+    playhead: Option<u32>,
+    since_hello: Duration,
+    /// Whether the host has stopped the match, after which the buffer is shown until it's empty.
+    stopped: bool,
+}
+
+impl Spectate {
+    /// Start watching the match with the given settings from the host of the given connection.
+    pub fn new(connection: Connection, settings: MatchSettings) -> Self {
+        Self {
+            court: Court::new(settings),
+            label: Text {
+                x: 0.5,
+                y: 0.95,
+                text: format!("WATCHING {}", connection.peer())
+                    .encode_utf16()
+                    .collect(),
+                size: TextSize::Tiny,
+            },
+            connection,
+            buffer: VecDeque::new(),
+            playhead: None,
+            since_hello: Duration::ZERO,
+            stopped: false,
+        }
+    }
+
+    /// Get the court which is shown to the spectator.
+    pub fn court(&self) -> &Court {
+        &self.court
+    }
+
+    /// Put the given state into the buffer unless a newer state has been shown already.
+    fn push(&mut self, snapshot: Snapshot) {
+        if self
+            .playhead
+            .is_some_and(|playhead| snapshot.steps <= playhead)
+        {
+            return;
+        }
+        let index = self
+            .buffer
+            .iter()
+            .position(|buffered| buffered.steps >= snapshot.steps)
+            .unwrap_or(self.buffer.len());
+        match self.buffer.get(index) {
+            Some(buffered) if buffered.steps == snapshot.steps => (),
+            _ => self.buffer.insert(index, snapshot),
+        }
+    }
+
+    /// Advance the shown step and show the newest state which has been reached.
+    ///
+    /// The shown step stays where it is while the buffer is empty or filling up.
+    fn play(&mut self) {
+        let (oldest, newest) = match (self.buffer.front(), self.buffer.back()) {
+            (Some(oldest), Some(newest)) => (oldest.steps, newest.steps),
+            _ => return,
+        };
+        let playhead = match self.playhead {
+            // Skip ahead when the states arrive faster than they are shown, e.g. after a stall.
+            Some(playhead) if playhead + 2 * DELAY_STEPS >= newest => playhead + 1,
+            // Fill the buffer before the first state is shown.
+            None if newest - oldest < DELAY_STEPS && !self.stopped => return,
+            _ => newest.saturating_sub(DELAY_STEPS),
+        };
+        let first = self.playhead.is_none();
+        self.playhead = Some(playhead);
+        let mut shown = None;
+        while self.buffer.front().is_some_and(|s| s.steps <= playhead) {
+            shown = self.buffer.pop_front();
+        }
+        match shown {
+            // The spectator may join in the middle of the match, so nothing is guessed from it.
+            Some(snapshot) if first => self.court.sync(&snapshot),
+            Some(snapshot) => self.court.restore(&snapshot),
+            None => (),
+        }
+    }
+}
+
+impl Scene for Spectate {
+    fn tick(&mut self, dt: Duration) -> Transition {
+        let messages = match self.connection.receive() {
+            Ok(messages) => messages,
+            Err(error) => return Transition::Replace(Box::new(Lobby::failed(error))),
+        };
+        for message in messages {
+            match message {
+                Message::State(snapshot) => self.push(snapshot),
+                Message::Bye => self.stopped = true,
+                Message::Hello | Message::Welcome(..) | Message::Input(_) => (),
+            }
+        }
+        if !self.stopped && self.connection.is_lost() {
+            let message = "THE CONNECTION TO THE HOST WAS LOST";
+            return Transition::Replace(Box::new(Lobby::failed(message)));
+        }
+        self.since_hello += dt;
+        if self.since_hello >= HELLO_INTERVAL {
+            self.since_hello = Duration::ZERO;
+            if let Err(error) = self.connection.send(&Message::Hello) {
+                return Transition::Replace(Box::new(Lobby::failed(error)));
+            }
+        }
+        self.play();
+        if self.court.is_over() {
+            return Transition::Replace(Box::new(EndGame::new(&self.court)));
+        }
+        if self.stopped && self.buffer.is_empty() {
+            let message = "THE HOST STOPPED THE MATCH";
+            return Transition::Replace(Box::new(Lobby::failed(message)));
+        }
+        Transition::Stay
+    }
+
+    fn draw(&self, ctx: &mut dyn Renderer, alpha: f32) {
+        self.court.draw(ctx, alpha);
+        ctx.draw_text(&self.label);
+    }
+
+    fn action_down(&mut self, _action: Action) -> Transition {
+        Transition::Stay
+    }
+
+    fn action_up(&mut self, action: Action) -> Transition {
+        match action {
+            Action::Back | Action::Pause => {
+                let _ = self.connection.send(&Message::Bye);
+                Transition::Replace(Box::new(MainMenu::new()))
+            }
+            _ => Transition::Stay,
+        }
+    }
+
+    fn take_sounds(&mut self) -> Vec<Sound> {
+        self.court.take_sounds()
+    }
+}
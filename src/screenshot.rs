@@ -0,0 +1,138 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+use crate::renderer::{Palette, Renderer};
+use crate::software::SoftwareRenderer;
+
+/// The width of the captured screenshots in pixels.
+pub const WIDTH: u32 = 1040;
+
+/// The height of the captured screenshots in pixels.
+pub const HEIGHT: u32 = 800;
+
+/// The level of the deflate compression of PNG images from zero to ten.
+const COMPRESSION_LEVEL: u8 = 6;
+
+/// Render a frame with the given palette, view aspect ratio and drawing function with a software
+/// renderer and save it into the given file.
+///
+/// The image is written as PPM if the file has a `ppm` extension and as PNG otherwise.
+pub fn save_with(
+    path: &Path,
+    palette: Palette,
+    aspect: f32,
+    draw: impl FnOnce(&mut dyn Renderer),
+) -> io::Result<()> {
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    renderer.set_palette(palette);
+    renderer.set_aspect(aspect);
+    renderer.clear();
+    draw(&mut renderer);
+    renderer.present();
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    let (width, height, pixels) = (renderer.width(), renderer.height(), renderer.pixels());
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("ppm") => {
+            write_ppm(&mut writer, width, height, pixels)?
+        }
+        _ => write_png(&mut writer, width, height, pixels)?,
+    }
+    writer.flush()
+}
+
+/// Write the given RGBA pixels as a binary PPM image.
+pub fn write_ppm(
+    writer: &mut impl Write,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+) -> io::Result<()> {
+    write!(writer, "P6\n{} {}\n255\n", width, height)?;
+    for pixel in pixels.chunks_exact(4) {
+        writer.write_all(&pixel[..3])?;
+    }
+    Ok(())
+}
+
+/// Write the given RGBA pixels as a PNG image.
+pub fn write_png(
+    writer: &mut impl Write,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+) -> io::Result<()> {
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    // Each scanline is prefixed with a filter type byte where zero means no filtering.
+    let stride = width as usize * 4;
+    let mut scanlines = Vec::with_capacity((stride + 1) * height as usize);
+    for row in pixels.chunks_exact(stride) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+
+    let data = compress_to_vec_zlib(&scanlines, COMPRESSION_LEVEL);
+
+    writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+    write_chunk(writer, b"IHDR", &header)?;
+    write_chunk(writer, b"IDAT", &data)?;
+    write_chunk(writer, b"IEND", &[])
+}
+
+/// Write a single PNG chunk with the given type and data.
+fn write_chunk(writer: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    writer.write_all(&crc32(kind.iter().chain(data)).to_be_bytes())
+}
+
+/// Calculate the CRC-32 checksum used with the PNG chunks.
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use miniz_oxide::inflate::decompress_to_vec_zlib;
+
+    #[test]
+    fn png_contains_the_compressed_scanlines() {
+        let pixels: Vec<u8> = (0..2 * 3 * 4).map(|i| i as u8).collect();
+        let mut png = Vec::new();
+        write_png(&mut png, 2, 3, &pixels).unwrap();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let header = &png[8..];
+        assert_eq!(&header[4..8], b"IHDR");
+        assert_eq!(&header[8..16], &[0, 0, 0, 2, 0, 0, 0, 3]);
+        let data = &header[25..];
+        let len = u32::from_be_bytes(data[..4].try_into().unwrap()) as usize;
+        assert_eq!(&data[4..8], b"IDAT");
+        let scanlines = decompress_to_vec_zlib(&data[8..8 + len]).unwrap();
+        let expected: Vec<u8> = pixels
+            .chunks_exact(8)
+            .flat_map(|row| std::iter::once(0).chain(row.iter().copied()))
+            .collect();
+        assert_eq!(scanlines, expected);
+    }
+}
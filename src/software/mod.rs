@@ -0,0 +1,167 @@
+use crate::geometry::{Rectangle, Text};
+use crate::renderer::{aspect_offset, Color, Palette, Renderer, ASPECT};
+
+mod font;
+
+/// The height of the capital letters relative to the font size.
+const CAP_HEIGHT: f32 = 0.65;
+
+/// A CPU based rendering context which draws into an in-memory RGBA framebuffer.
+pub struct SoftwareRenderer {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    color: Color,
+    palette: Palette,
+    aspect: f32,
+}
+
+impl SoftwareRenderer {
+    /// Build a new software renderer with a framebuffer of the given size.
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut renderer = Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+            color: Color::WHITE,
+            palette: Palette::default(),
+            aspect: ASPECT,
+        };
+        renderer.clear();
+        renderer
+    }
+
+    /// Get the width of the framebuffer in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the framebuffer in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the framebuffer contents as rows of RGBA pixels starting from the top-left corner.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Get the RGBA color of the pixel at the given coordinates.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        let mut color = [0; 4];
+        color.copy_from_slice(&self.pixels[index..index + 4]);
+        color
+    }
+
+    /// Get the letterbox offset and the size of the view inside the framebuffer.
+    fn view(&self) -> (f32, f32, f32, f32) {
+        let (x, y) = aspect_offset(self.width, self.height, self.aspect);
+        (
+            x,
+            y,
+            self.width as f32 - x * 2.0,
+            self.height as f32 - y * 2.0,
+        )
+    }
+
+    /// Blend the current color over the pixels whose centers are inside the given rectangle in
+    /// framebuffer coordinates.
+    fn fill(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        let color = self.color;
+        let alpha = color.a.clamp(0.0, 1.0);
+        let x0 = (x.round().max(0.0) as u32).min(self.width);
+        let y0 = (y.round().max(0.0) as u32).min(self.height);
+        let x1 = ((x + w).round().max(0.0) as u32).min(self.width);
+        let y1 = ((y + h).round().max(0.0) as u32).min(self.height);
+        for row in y0..y1 {
+            let start = (row as usize * self.width as usize + x0 as usize) * 4;
+            let end = (row as usize * self.width as usize + x1 as usize) * 4;
+            for pixel in self.pixels[start..end].chunks_exact_mut(4) {
+                for (channel, value) in pixel.iter_mut().zip([color.r, color.g, color.b]) {
+                    let value = value.clamp(0.0, 1.0) * 255.0;
+                    *channel = (value * alpha + *channel as f32 * (1.0 - alpha)).round() as u8;
+                }
+            }
+        }
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn clear(&mut self) {
+        let background = self.palette.background;
+        let rgba = [background.r, background.g, background.b, 1.0].map(|c| (c * 255.0) as u8);
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&rgba);
+        }
+        self.color = self.palette.foreground;
+    }
+
+    fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    fn palette(&self) -> Palette {
+        self.palette
+    }
+
+    fn set_aspect(&mut self, aspect: f32) {
+        self.aspect = aspect;
+    }
+
+    fn aspect(&self) -> f32 {
+        self.aspect
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    fn draw_rectangle(&mut self, rectangle: &Rectangle) {
+        let (x, y, w, h) = self.view();
+        self.fill(
+            x + rectangle.x * w,
+            y + rectangle.y * h,
+            rectangle.w * w,
+            rectangle.h * h,
+        );
+    }
+
+    fn draw_text(&mut self, text: &Text) {
+        let (x, y, w, h) = self.view();
+        let chars: Vec<char> = char::decode_utf16(text.text.iter().copied())
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect();
+        if chars.is_empty() {
+            return;
+        }
+
+        // Texts are centered around their position just like with the Direct2D renderer.
+        let unit = text.size.scale() * h * CAP_HEIGHT / font::GLYPH_HEIGHT as f32;
+        let advance = (font::GLYPH_WIDTH + 1) as f32 * unit;
+        let width = advance * chars.len() as f32 - unit;
+        let left = x + text.x * w - width / 2.0;
+        let top = y + text.y * h - font::GLYPH_HEIGHT as f32 * unit / 2.0;
+        for (i, c) in chars.iter().enumerate() {
+            let glyph_left = left + advance * i as f32;
+            for (row, bits) in font::glyph(*c).iter().enumerate() {
+                for col in 0..font::GLYPH_WIDTH {
+                    if bits & (1 << (font::GLYPH_WIDTH - 1 - col)) != 0 {
+                        self.fill(
+                            glyph_left + col as f32 * unit,
+                            top + row as f32 * unit,
+                            unit,
+                            unit,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn present(&mut self) {}
+}
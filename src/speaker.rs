@@ -0,0 +1,62 @@
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::HINSTANCE;
+use windows::Win32::Media::Audio::{PlaySoundW, SND_ASYNC, SND_MEMORY, SND_NODEFAULT};
+
+use crate::audio::{synthesize, write_wav, AudioSink, Sound, SAMPLE_RATE};
+use crate::codec;
+
+/// An audio sink which plays the sounds through the default audio device of Windows.
+///
+/// A new sound stops the sound which is still playing, which suits the short blips of the game.
+pub struct Speaker {
+    /// The sounds as WAV files in memory in the order of `Sound::ALL`.
+    ///
+    /// The files must outlive the playback as it continues after the sound has been started.
+    files: Vec<Vec<u8>>,
+}
+
+impl Speaker {
+    pub fn new() -> Self {
+        let files = Sound::ALL
+            .iter()
+            .map(|sound| {
+                let mut file = Vec::new();
+                codec::append(&mut file, |file| {
+                    write_wav(file, &synthesize(*sound, SAMPLE_RATE), SAMPLE_RATE)
+                });
+                file
+            })
+            .collect();
+        Self { files }
+    }
+}
+
+impl Default for Speaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioSink for Speaker {
+    fn play(&mut self, sound: Sound) {
+        let Some(index) = Sound::ALL.iter().position(|s| *s == sound) else {
+            return;
+        };
+        let file = PCWSTR(self.files[index].as_ptr() as *const u16);
+        // A missing audio device only keeps the game silent.
+        unsafe {
+            PlaySoundW(
+                file,
+                HINSTANCE::default(),
+                SND_MEMORY | SND_ASYNC | SND_NODEFAULT,
+            )
+        };
+    }
+}
+
+impl Drop for Speaker {
+    /// Stop the playback before the files are released.
+    fn drop(&mut self) {
+        unsafe { PlaySoundW(PCWSTR::null(), HINSTANCE::default(), SND_NODEFAULT) };
+    }
+}
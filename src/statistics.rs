@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use crate::events::{GameEvent, Subscriber};
+use crate::scenes::Side;
+
+/// The statistics of a match which are collected from the events on the court.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Statistics {
+    /// The amount of paddle hits in each finished rally in the order they were played.
+    pub rallies: Vec<u32>,
+    /// The amount of times each player returned the ball.
+    pub l_hits: u32,
+    pub r_hits: u32,
+    /// The amount of points each player scored over all sets.
+    pub l_goals: u32,
+    pub r_goals: u32,
+    pub serves: u32,
+    /// The highest speed of the ball after a paddle hit.
+    pub top_speed: f32,
+    pub duration: Duration,
+    /// Whether the events were guessed from the states of another machine, which makes the
+    /// statistics approximate.
+    pub approximate: bool,
+    /// The amount of paddle hits in the rally which is being played.
+    rally: u32,
+}
+
+impl Statistics {
+    /// Get the amount of paddle hits in the longest finished rally.
+    pub fn longest_rally(&self) -> u32 {
+        self.rallies.iter().copied().max().unwrap_or_default()
+    }
+
+    /// Get the average amount of paddle hits in the finished rallies.
+    pub fn average_rally(&self) -> f32 {
+        match self.rallies.len() {
+            0 => 0.0,
+            count => self.rallies.iter().sum::<u32>() as f32 / count as f32,
+        }
+    }
+
+    /// Get the share of the serves which were won by the left and right player.
+    ///
+    /// A serve is won by the player who scores the point which ends it. The serves which end
+    /// without a point, e.g. when the time limit ends the match during a rally, are not counted,
+    /// so the shares add up to one.
+    pub fn serves_won(&self) -> (f32, f32) {
+        match self.l_goals + self.r_goals {
+            0 => (0.0, 0.0),
+            won => (
+                self.l_goals as f32 / won as f32,
+                self.r_goals as f32 / won as f32,
+            ),
+        }
+    }
+}
+
+impl Subscriber for Statistics {
+    fn notify(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::ServeStarted => {
+                self.serves += 1;
+                self.rally = 0;
+            }
+            GameEvent::PaddleHit { side, speed } => {
+                match side {
+                    Side::Left => self.l_hits += 1,
+                    Side::Right => self.r_hits += 1,
+                }
+                self.rally += 1;
+                self.top_speed = self.top_speed.max(speed);
+            }
+            GameEvent::Goal { scorer } => {
+                match scorer {
+                    Side::Left => self.l_goals += 1,
+                    Side::Right => self.r_goals += 1,
+                }
+                self.rallies.push(self.rally);
+                self.rally = 0;
+            }
+            GameEvent::WallBounce | GameEvent::MatchEnded => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serve_without_a_point_is_not_won() {
+        let mut statistics = Statistics::default();
+        let events = [
+            GameEvent::ServeStarted,
+            GameEvent::Goal { scorer: Side::Left },
+            GameEvent::ServeStarted,
+            GameEvent::Goal { scorer: Side::Left },
+            GameEvent::ServeStarted,
+            GameEvent::Goal {
+                scorer: Side::Right,
+            },
+            GameEvent::ServeStarted,
+            GameEvent::MatchEnded,
+        ];
+        for event in &events {
+            statistics.notify(event);
+        }
+        assert_eq!(statistics.serves, 4);
+        assert_eq!(statistics.serves_won(), (2.0 / 3.0, 1.0 / 3.0));
+    }
+}
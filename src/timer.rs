@@ -1,8 +1,15 @@
 use std::time::{Duration, Instant};
 
-/// A timer that can be used to calculate split times for ticks etc.
+/// The fixed duration of a single simulation step (i.e. 120 steps per second).
+pub const STEP: Duration = Duration::from_nanos(1_000_000_000 / 120);
+
+/// The maximum amount of accumulated time to avoid an endless catch-up after a long stall.
+const MAX_ACCUMULATED: Duration = Duration::from_millis(250);
+
+/// A timer that can be used to calculate split times and to run fixed simulation steps.
 pub struct Timer {
     time: Instant,
+    accumulated: Duration,
 }
 
 impl Timer {
@@ -10,6 +17,7 @@ impl Timer {
     pub fn new() -> Self {
         Self {
             time: Instant::now(),
+            accumulated: Duration::ZERO,
         }
     }
 
@@ -20,4 +28,75 @@ impl Timer {
         self.time = now;
         duration
     }
+
+    /// Add the duration passed since the previous time call into the accumulated time.
+    pub fn accumulate(&mut self) {
+        let time = self.time();
+        self.add(time);
+    }
+
+    /// Add the given duration into the accumulated time without exceeding the maximum.
+    fn add(&mut self, duration: Duration) {
+        self.accumulated = Duration::min(self.accumulated + duration, MAX_ACCUMULATED);
+    }
+
+    /// Consume a single simulation step from the accumulated time if enough time has passed.
+    pub fn consume(&mut self) -> bool {
+        if self.accumulated < STEP {
+            return false;
+        }
+        self.accumulated -= STEP;
+        true
+    }
+
+    /// Get the progress from the previous to the next simulation step in range [0, 1).
+    pub fn alpha(&self) -> f32 {
+        self.accumulated.as_secs_f32() / STEP.as_secs_f32()
+    }
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Consume all whole steps from the accumulated time and get their amount.
+    fn consume_all(timer: &mut Timer) -> u32 {
+        let mut steps = 0;
+        while timer.consume() {
+            steps += 1;
+        }
+        steps
+    }
+
+    #[test]
+    fn short_frames_add_up_to_whole_steps() {
+        let mut timer = Timer::new();
+        let mut steps = 0;
+        for _ in 0..10 {
+            timer.add(Duration::from_millis(3));
+            steps += consume_all(&mut timer);
+        }
+        // The 30 milliseconds contain three whole steps and 60 % of the next one.
+        assert_eq!(steps, 3);
+        assert!((timer.alpha() - 0.6).abs() < 1e-4, "{}", timer.alpha());
+    }
+
+    #[test]
+    fn long_frame_is_consumed_over_several_steps() {
+        let mut timer = Timer::new();
+        timer.add(STEP * 5 + STEP / 4);
+        assert_eq!(consume_all(&mut timer), 5);
+        assert!((timer.alpha() - 0.25).abs() < 1e-4, "{}", timer.alpha());
+
+        // A stall longer than the maximum is only caught up to the maximum.
+        timer.add(Duration::from_secs(2));
+        assert_eq!(consume_all(&mut timer), 30);
+        assert!(timer.alpha() < 1.0);
+    }
 }
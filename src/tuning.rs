@@ -0,0 +1,131 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::{config, renderer::ASPECT, scenes::Physics};
+
+/// The file where designers can override the gameplay tunables.
+pub const TUNING_FILE: &str = "tuning.toml";
+
+/// The tunables of the gameplay and the view which can be changed without recompiling.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tuning {
+    /// The physical properties which are scaled with the options of each match.
+    pub physics: Physics,
+    /// The aspect ratio (width / height) of the view.
+    pub aspect: f32,
+}
+
+impl Tuning {
+    /// Load the tunables from the given file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let source = fs::read_to_string(path)?;
+        Self::parse(&source).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Parse the tunables from the contents of a tuning file.
+    ///
+    /// The tunables which are not defined in the file use their default values.
+    pub fn parse(source: &str) -> Result<Self, config::Error> {
+        let mut tuning = Self::default();
+        let physics = &mut tuning.physics;
+        // The line of the latest physical property, where the properties are checked together.
+        let mut physics_line = 0;
+        for entry in config::parse(source)? {
+            let float = |range| entry.as_float(range);
+            match (entry.section.as_str(), entry.key.as_str()) {
+                ("physics", key) => {
+                    physics_line = entry.line;
+                    match key {
+                        "paddle_velocity" => {
+                            physics.paddle_velocity = float(Physics::PADDLE_VELOCITY_RANGE)?
+                        }
+                        "paddle_height" => {
+                            physics.paddle_height = float(Physics::PADDLE_HEIGHT_RANGE)?
+                        }
+                        "ball_velocity" => {
+                            physics.ball_velocity = float(Physics::BALL_VELOCITY_RANGE)?
+                        }
+                        "ball_velocity_scalar" => {
+                            physics.ball_velocity_scalar =
+                                float(Physics::BALL_VELOCITY_SCALAR_RANGE)?
+                        }
+                        "ball_max_velocity" => {
+                            physics.ball_max_velocity = float(Physics::BALL_VELOCITY_RANGE)?
+                        }
+                        "max_bounce_angle" => {
+                            physics.max_bounce_angle =
+                                float(Physics::MAX_BOUNCE_DEGREES_RANGE)?.to_radians()
+                        }
+                        "countdown" => {
+                            let range = Physics::COUNTDOWN_RANGE;
+                            let millis = entry.as_integer(
+                                range.start().as_millis() as i64,
+                                range.end().as_millis() as i64,
+                            )?;
+                            physics.countdown = Duration::from_millis(millis as u64);
+                        }
+                        "nudge" => physics.nudge = float(Physics::NUDGE_RANGE)?,
+                        _ => return Err(entry.unknown("tunable")),
+                    }
+                }
+                ("view", "aspect") => tuning.aspect = float(0.5..=3.0)?,
+                _ => return Err(entry.unknown("tunable")),
+            }
+        }
+        if let Err(message) = physics.validate() {
+            return Err(config::Error {
+                line: physics_line,
+                message,
+            });
+        }
+        Ok(tuning)
+    }
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning {
+            physics: Physics::default(),
+            aspect: ASPECT,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_tunable_is_reported_with_its_section() {
+        let error = Tuning::parse("[view]\naspect = 1.5\nzoom = 2.0\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 3: unknown tunable 'view.zoom'");
+    }
+
+    #[test]
+    fn physics_are_checked_together() {
+        let error = Tuning::parse("[physics]\nball_velocity = 0.002\nball_max_velocity = 0.001\n")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 3: ball_max_velocity must not be lower than ball_velocity"
+        );
+        let error = Tuning::parse("[physics]\nnudge = 0.01\npaddle_height = 0.95\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 3: paddle_height must leave room for the nudge between the walls"
+        );
+        let error = Tuning::parse("[physics]\nball_velocity_scalar = 0.5\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2: invalid value for physics.ball_velocity_scalar: expected a number from 1 to 2"
+        );
+    }
+
+    #[test]
+    fn view_aspect_is_tuned() {
+        let tuning = Tuning::parse("[view]\naspect = 1.5\n").unwrap();
+        assert_eq!(tuning.aspect, 1.5);
+    }
+}
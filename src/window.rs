@@ -0,0 +1,152 @@
+use rust_pong::app::App;
+use rust_pong::gamepad::XInputController;
+use rust_pong::graphics::Graphics;
+use rust_pong::input::{InputEvent, Key};
+use rust_pong::scenes::Scene;
+use rust_pong::speaker::Speaker;
+use rust_pong::tuning::Tuning;
+use windows::core::Result;
+use windows::s;
+use windows::Win32::Foundation::*;
+use windows::Win32::System::LibraryLoader::GetModuleHandleA;
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+/// Create the application window and run the message loop from the given scene with the given
+/// tunables until the application exits.
+pub fn run(scene: Box<dyn Scene>, tuning: Tuning) -> Result<()> {
+    let window = create_window();
+    let gfx = Graphics::new(window)?;
+    let mut app = App::with_scene(Box::new(gfx), tuning, scene)
+        .with_source(Box::new(XInputController::new()))
+        .with_audio(Box::new(Speaker::new()));
+    let mut msg = MSG::default();
+    unsafe { SetWindowLongPtrA(window, GWLP_USERDATA, &mut app as *mut _ as _) };
+    while app.running() {
+        unsafe {
+            // Check and acquire system messages from the message queue.
+            while PeekMessageA(&mut msg, HWND(0), 0, 0, PM_REMOVE).into() {
+                if msg.message == WM_QUIT {
+                    return Ok(());
+                }
+                TranslateMessage(&msg);
+                DispatchMessageA(&msg);
+            }
+        }
+        app.tick();
+        app.draw();
+    }
+    Ok(())
+}
+
+fn create_window() -> HWND {
+    unsafe {
+        // Acquire the module handle of the application.
+        let instance = GetModuleHandleA(None).unwrap();
+        debug_assert!(!instance.is_invalid());
+
+        // Register a window class for the application.
+        let class_name = s!("window");
+        let class_result = RegisterClassA(&WNDCLASSA {
+            hCursor: LoadCursorW(None, IDC_ARROW).unwrap(),
+            hInstance: instance,
+            lpszClassName: class_name,
+            style: CS_HREDRAW | CS_VREDRAW,
+            lpfnWndProc: Some(wndproc),
+            ..Default::default()
+        });
+        debug_assert!(class_result != 0);
+
+        // Build the application window.
+        CreateWindowExA(
+            WINDOW_EX_STYLE::default(),
+            class_name,
+            s!("Pong"),
+            WS_OVERLAPPEDWINDOW | WS_VISIBLE,
+            CW_USEDEFAULT,
+            CW_USEDEFAULT,
+            CW_USEDEFAULT,
+            CW_USEDEFAULT,
+            None,
+            None,
+            instance,
+            None,
+        )
+    }
+}
+
+/// A message router for the incoming operating system messages for the application.
+unsafe extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    let app = GetWindowLongPtrA(hwnd, GWLP_USERDATA) as *mut App;
+    if !app.is_null() {
+        match msg {
+            WM_DESTROY => {
+                PostQuitMessage(0);
+                return LRESULT(0);
+            }
+            WM_SIZE => {
+                (*app).resize();
+                return LRESULT(0);
+            }
+            WM_KEYDOWN => {
+                if let Some(key) = map_key(wparam.0 as u16) {
+                    (*app).input(InputEvent::KeyDown(key));
+                }
+                return LRESULT(0);
+            }
+            WM_KEYUP => {
+                if let Some(key) = map_key(wparam.0 as u16) {
+                    (*app).input(InputEvent::KeyUp(key));
+                }
+                return LRESULT(0);
+            }
+            WM_MOUSEMOVE => {
+                let (x, y) = mouse_position(lparam);
+                (*app).input(InputEvent::MouseMove { x, y });
+                return LRESULT(0);
+            }
+            WM_LBUTTONDOWN => {
+                let (x, y) = mouse_position(lparam);
+                (*app).input(InputEvent::MouseClick { x, y });
+                return LRESULT(0);
+            }
+            _ => (),
+        }
+    }
+    DefWindowProcA(hwnd, msg, wparam, lparam)
+}
+
+/// Map the given Win32 virtual-key code into a platform-neutral key.
+fn map_key(key: u16) -> Option<Key> {
+    match VIRTUAL_KEY(key) {
+        VK_UP => Some(Key::Up),
+        VK_DOWN => Some(Key::Down),
+        VK_LEFT => Some(Key::Left),
+        VK_RIGHT => Some(Key::Right),
+        VK_RETURN => Some(Key::Enter),
+        VK_ESCAPE => Some(Key::Escape),
+        VK_SPACE => Some(Key::Space),
+        VK_TAB => Some(Key::Tab),
+        VK_BACK => Some(Key::Backspace),
+        VK_SHIFT => Some(Key::Shift),
+        VK_CONTROL => Some(Key::Control),
+        VK_OEM_PERIOD | VK_DECIMAL => Some(Key::Period),
+        VK_F12 => Some(Key::F12),
+        // The codes of the letter and digit keys match their uppercase ASCII characters.
+        VIRTUAL_KEY(code @ (0x30..=0x39 | 0x41..=0x5A)) => {
+            Key::from_label(&char::from(code as u8).to_string())
+        }
+        // The digits of the number pad act as the digit keys of the main keyboard.
+        VIRTUAL_KEY(code) if (VK_NUMPAD0.0..=VK_NUMPAD9.0).contains(&code) => {
+            map_key(code - VK_NUMPAD0.0 + 0x30)
+        }
+        _ => None,
+    }
+}
+
+/// Get the cursor position in the client area from the parameter of a Win32 mouse message.
+fn mouse_position(lparam: LPARAM) -> (i32, i32) {
+    let x = (lparam.0 & 0xFFFF) as i16;
+    let y = ((lparam.0 >> 16) & 0xFFFF) as i16;
+    (x.into(), y.into())
+}
@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use rust_pong::{
+    audio::{RecordingSink, Sound},
+    events::GameEvent,
+    headless::Simulation,
+    rules::MatchRules,
+    scenes::{MatchSettings, Mode},
+};
+
+#[test]
+fn goals_and_paddle_hits_are_heard_on_their_step() {
+    let settings = MatchSettings {
+        mode: Mode::Demo,
+        rules: MatchRules::PRESETS[1].1,
+        seed: 11,
+        ..MatchSettings::default()
+    };
+    let sink = Rc::new(RefCell::new(RecordingSink::default()));
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let published = events.clone();
+    let mut simulation = Simulation::new(settings, Vec::new())
+        .with_audio(sink.clone())
+        .with_subscriber(Rc::new(RefCell::new(move |event: &GameEvent| {
+            published.borrow_mut().push(*event)
+        })));
+
+    // Each event is tagged with the step during which it was published.
+    let mut expected = Vec::new();
+    let mut step = 0;
+    while !simulation.court().is_over() {
+        assert!(
+            simulation.time() < Duration::from_secs(60 * 60),
+            "the match did not end"
+        );
+        simulation.step();
+        for event in events.borrow_mut().drain(..) {
+            match event {
+                GameEvent::Goal { .. } => expected.push((step, Sound::Score)),
+                GameEvent::PaddleHit { .. } => expected.push((step, Sound::PaddleHit)),
+                _ => (),
+            }
+        }
+        step += 1;
+    }
+
+    let sink = sink.borrow();
+    let heard: Vec<_> = sink
+        .played()
+        .iter()
+        .copied()
+        .filter(|(_, sound)| matches!(sound, Sound::Score | Sound::PaddleHit))
+        .collect();
+    assert!(expected.iter().any(|(_, sound)| *sound == Sound::Score));
+    assert!(expected.iter().any(|(_, sound)| *sound == Sound::PaddleHit));
+    assert_eq!(heard, expected);
+}
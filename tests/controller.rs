@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use rust_pong::{
+    controller::SimulatedController,
+    headless::Simulation,
+    input::Player,
+    scenes::{MatchSettings, Mode, Physics},
+    timer::STEP,
+};
+
+/// The amount of steps the paddles are moved in each test.
+const STEPS: u32 = 10;
+
+/// Steer the paddles with the given raw stick positions and get the distances the paddles moved.
+fn steer(l_axis: f32, r_axis: f32) -> (f32, f32) {
+    // The paddles stand still during the countdown.
+    let settings = MatchSettings {
+        mode: Mode::TwoPlayers,
+        physics: Physics {
+            countdown: Duration::ZERO,
+            ..Physics::default()
+        },
+        ..MatchSettings::default()
+    };
+    let mut simulation = Simulation::new(settings, Vec::new());
+    let mut controller = SimulatedController::new(0.25);
+    controller.set_axis(Player::One, l_axis);
+    controller.set_axis(Player::Two, r_axis);
+    let before = simulation.court().snapshot();
+    for _ in 0..STEPS {
+        simulation.poll(&mut controller);
+        simulation.step();
+    }
+    let after = simulation.court().snapshot();
+    (
+        after.l_paddle_y - before.l_paddle_y,
+        after.r_paddle_y - before.r_paddle_y,
+    )
+}
+
+/// Get the distance a paddle moves in the test with the given share of its full speed.
+fn distance(speed: f32) -> f32 {
+    let millis = (STEP * STEPS).as_secs_f32() * 1000.0;
+    speed * MatchSettings::default().physics.paddle_velocity * millis
+}
+
+#[test]
+fn full_deflection_moves_the_paddles_with_full_speed() {
+    let (l, r) = steer(-1.0, 1.0);
+    assert!((l + distance(1.0)).abs() < 1e-5, "{}", l);
+    assert!((r - distance(1.0)).abs() < 1e-5, "{}", r);
+}
+
+#[test]
+fn partial_deflection_moves_the_paddles_slower() {
+    let (l, r) = steer(0.625, -0.4);
+    assert!((l - distance(0.5)).abs() < 1e-5, "{}", l);
+    assert!((r + distance(0.2)).abs() < 1e-5, "{}", r);
+}
+
+#[test]
+fn deflection_within_the_dead_zone_keeps_the_paddles_still() {
+    assert_eq!(steer(0.2, -0.25), (0.0, 0.0));
+}
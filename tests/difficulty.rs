@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use rust_pong::{
+    ai::Difficulty,
+    headless::Simulation,
+    scenes::{MatchSettings, Mode},
+};
+
+/// The amount of seeded matches which are played with each difficulty.
+const MATCHES: u64 = 10;
+
+/// The maximum amount of simulated time for a single match.
+const TIME_LIMIT: Duration = Duration::from_secs(60 * 60);
+
+/// Get the amount of the seeded matches which the given difficulty wins against the reference bot.
+fn wins(difficulty: Difficulty) -> u32 {
+    let mut wins = 0;
+    for seed in 0..MATCHES {
+        let settings = MatchSettings {
+            mode: Mode::Demo,
+            difficulty,
+            seed,
+            ..MatchSettings::default()
+        };
+        let result = Simulation::new(settings, Vec::new()).run(TIME_LIMIT);
+        assert!(
+            result.finished,
+            "{:?} match {} did not end",
+            difficulty, seed
+        );
+        if result.l_points > result.r_points {
+            wins += 1;
+        }
+    }
+    wins
+}
+
+#[test]
+fn harder_difficulties_win_more_against_the_reference_bot() {
+    let wins = Difficulty::ALL.map(wins);
+    assert!(
+        wins.windows(2).all(|pair| pair[0] < pair[1]),
+        "wins from easy to impossible: {:?}",
+        wins
+    );
+}
+
+#[test]
+fn computer_players_repeat_the_match_for_the_same_seed() {
+    let settings = MatchSettings {
+        mode: Mode::Demo,
+        difficulty: Difficulty::Easy,
+        seed: 3,
+        ..MatchSettings::default()
+    };
+    let play = || {
+        let mut simulation = Simulation::new(settings, Vec::new());
+        assert!(simulation.run(TIME_LIMIT).finished);
+        let court = simulation.court();
+        (court.points(), court.steps(), court.snapshot().checksum())
+    };
+    assert_eq!(play(), play());
+}
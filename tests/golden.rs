@@ -0,0 +1,125 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use miniz_oxide::inflate::decompress_to_vec_zlib;
+use rust_pong::{
+    context::Context,
+    headless::Simulation,
+    renderer::{Palette, Renderer, ASPECT},
+    rules::MatchRules,
+    scenes::{EndGame, MainMenu, MatchSettings, Mode, Scene},
+    screenshot::write_png,
+    software::SoftwareRenderer,
+};
+
+/// The width of the reference frames in pixels.
+const WIDTH: u32 = 520;
+
+/// The height of the reference frames in pixels.
+const HEIGHT: u32 = 400;
+
+/// The environment variable which rewrites the reference frames instead of comparing them.
+const UPDATE: &str = "UPDATE_GOLDEN";
+
+/// Render the given scene with the default palette and view aspect ratio.
+fn render(scene: &dyn Scene) -> SoftwareRenderer {
+    let mut renderer = SoftwareRenderer::new(WIDTH, HEIGHT);
+    renderer.set_palette(Palette::default());
+    renderer.set_aspect(ASPECT);
+    renderer.clear();
+    scene.draw(&mut renderer, 1.0);
+    renderer.present();
+    renderer
+}
+
+/// Decode the RGBA pixels of an unfiltered PNG image which was written by the screenshots.
+fn decode(png: &[u8]) -> (u32, u32, Vec<u8>) {
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    let (mut width, mut height, mut data) = (0, 0, Vec::new());
+    let mut chunks = &png[8..];
+    while chunks.len() >= 12 {
+        let len = u32::from_be_bytes(chunks[..4].try_into().unwrap()) as usize;
+        let (kind, body) = (&chunks[4..8], &chunks[8..8 + len]);
+        match kind {
+            b"IHDR" => {
+                width = u32::from_be_bytes(body[..4].try_into().unwrap());
+                height = u32::from_be_bytes(body[4..8].try_into().unwrap());
+            }
+            b"IDAT" => data.extend_from_slice(body),
+            _ => (),
+        }
+        chunks = &chunks[12 + len..];
+    }
+    let scanlines = decompress_to_vec_zlib(&data).expect("invalid image data");
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+    for row in scanlines.chunks_exact(width as usize * 4 + 1) {
+        assert_eq!(row[0], 0, "only unfiltered scanlines are supported");
+        pixels.extend_from_slice(&row[1..]);
+    }
+    (width, height, pixels)
+}
+
+/// Compare the rendered frame with the reference frame of the given name.
+///
+/// The reference frame is written instead when the update environment variable is set.
+fn assert_golden(name: &str, renderer: &SoftwareRenderer) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.png", name));
+    if std::env::var_os(UPDATE).is_some() {
+        let mut png = Vec::new();
+        write_png(&mut png, WIDTH, HEIGHT, renderer.pixels()).unwrap();
+        fs::write(&path, png).unwrap();
+        return;
+    }
+
+    let png = fs::read(&path).unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e));
+    let (width, height, pixels) = decode(&png);
+    assert_eq!((width, height), (WIDTH, HEIGHT), "size of {}", name);
+    let different = pixels
+        .chunks_exact(4)
+        .zip(renderer.pixels().chunks_exact(4))
+        .filter(|(expected, actual)| expected != actual)
+        .count();
+    assert_eq!(
+        different, 0,
+        "{} pixels of {} differ from the reference frame, run with {}=1 to update it",
+        different, name, UPDATE
+    );
+}
+
+/// Build the settings of the seeded match which is shown in the reference frames.
+fn settings() -> MatchSettings {
+    MatchSettings {
+        mode: Mode::Demo,
+        rules: MatchRules::PRESETS[1].1,
+        seed: 7,
+        ..MatchSettings::default()
+    }
+}
+
+#[test]
+fn main_menu_matches_its_reference_frame() {
+    let mut menu = MainMenu::new();
+    menu.enter(&Context::default());
+    assert_golden("main_menu", &render(&menu));
+}
+
+#[test]
+fn court_matches_its_reference_frame() {
+    let mut simulation = Simulation::new(settings(), Vec::new());
+    while simulation.time() < Duration::from_millis(1500) {
+        simulation.step();
+    }
+    assert_golden("court", &render(simulation.court()));
+}
+
+#[test]
+fn end_game_matches_its_reference_frame() {
+    let mut simulation = Simulation::new(settings(), Vec::new());
+    assert!(simulation.run(Duration::from_secs(60 * 60)).finished);
+    let mut end_game = EndGame::new(simulation.court());
+    end_game.enter(&Context::default());
+    assert_golden("end_game", &render(&end_game));
+}
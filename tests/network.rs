@@ -0,0 +1,276 @@
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rust_pong::{
+    input::Player,
+    network::{decode, encode, Connection, Listener, Message, Netcode, Spectators, MAX_SPECTATORS},
+    rollback::{Session, UdpTransport},
+    rules::MatchRules,
+    scenes::{Court, MatchSettings, Mode, Physics, Scene, Spectate, DELAY_STEPS},
+    timer::STEP,
+};
+
+/// The time after which a message which was sent over the loopback is considered lost.
+const PATIENCE: Duration = Duration::from_secs(2);
+
+/// Receive messages from the given connection until one of them is accepted by the given function.
+fn wait_for<T>(connection: &mut Connection, mut accept: impl FnMut(Message) -> Option<T>) -> T {
+    let start = Instant::now();
+    while start.elapsed() < PATIENCE {
+        for message in connection.receive().unwrap() {
+            if let Some(value) = accept(message) {
+                return value;
+            }
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    panic!("no expected message arrived over the loopback");
+}
+
+/// Connect a client to a match with the given netcode over the loopback address and return the
+/// connections of both ends.
+fn connect(settings: MatchSettings, netcode: Netcode) -> (Connection, Connection) {
+    let mut listener = Listener::bind(0).unwrap();
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, listener.port().unwrap()));
+    let mut client = Connection::connect(address).unwrap();
+    let start = Instant::now();
+    let host = loop {
+        assert!(
+            start.elapsed() < PATIENCE,
+            "the host never accepted the client"
+        );
+        client.send(&Message::Hello).unwrap();
+        thread::sleep(Duration::from_millis(1));
+        if let Some(host) = listener.accept(&settings, netcode).unwrap() {
+            break host;
+        }
+    };
+    (host, client)
+}
+
+/// Let a spectator say hello to the given spectators until it is accepted as one more watcher.
+fn watch(spectators: &mut Spectators) -> Connection {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, spectators.port().unwrap()));
+    let mut spectator = Connection::connect(address).unwrap();
+    let count = spectators.count();
+    let start = Instant::now();
+    while spectators.count() == count {
+        assert!(
+            start.elapsed() < PATIENCE,
+            "the host never accepted the spectator"
+        );
+        spectator.send(&Message::Hello).unwrap();
+        thread::sleep(Duration::from_millis(1));
+        spectators.update().unwrap();
+    }
+    spectator
+}
+
+#[test]
+fn client_receives_the_settings_of_the_host() {
+    let settings = MatchSettings {
+        mode: Mode::TwoPlayers,
+        seed: 42,
+        ..MatchSettings::default()
+    };
+    for netcode in Netcode::ALL {
+        let (_host, mut client) = connect(settings, netcode);
+        let received = wait_for(&mut client, |message| match message {
+            Message::Welcome(settings, netcode) => Some((settings, netcode)),
+            _ => None,
+        });
+        assert_eq!(received, (settings, netcode));
+    }
+}
+
+#[test]
+fn snapshots_and_inputs_round_trip() {
+    let settings = MatchSettings {
+        mode: Mode::TwoPlayers,
+        ..MatchSettings::default()
+    };
+    let (mut host, mut client) = connect(settings, Netcode::Authoritative);
+    let mut court = Court::new(settings);
+    for _ in 0..200 {
+        court.update(Duration::from_millis(10));
+    }
+    let snapshot = court.snapshot();
+    host.send(&Message::State(snapshot)).unwrap();
+    let received = wait_for(&mut client, |message| match message {
+        Message::State(snapshot) => Some(snapshot),
+        _ => None,
+    });
+    assert_eq!(received, snapshot);
+
+    client.send(&Message::Input(-0.5)).unwrap();
+    let received = wait_for(&mut host, |message| match message {
+        Message::Input(movement) => Some(movement),
+        _ => None,
+    });
+    assert_eq!(received, -0.5);
+}
+
+#[test]
+fn spectators_beyond_the_limit_are_refused() {
+    let settings = MatchSettings::default();
+    let mut spectators = Spectators::bind(0, settings).unwrap();
+    let mut watchers: Vec<_> = (0..MAX_SPECTATORS)
+        .map(|_| watch(&mut spectators))
+        .collect();
+    for watcher in &mut watchers {
+        let received = wait_for(watcher, |message| match message {
+            Message::Welcome(settings, netcode) => Some((settings, netcode)),
+            _ => None,
+        });
+        assert_eq!(received, (settings, Netcode::Authoritative));
+    }
+
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, spectators.port().unwrap()));
+    let mut extra = Connection::connect(address).unwrap();
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_millis(200) {
+        extra.send(&Message::Hello).unwrap();
+        thread::sleep(Duration::from_millis(1));
+        spectators.update().unwrap();
+        assert!(extra.receive().unwrap().is_empty());
+    }
+    assert_eq!(spectators.count(), MAX_SPECTATORS);
+}
+
+#[test]
+fn late_spectator_is_shown_the_delayed_state() {
+    let settings = MatchSettings {
+        mode: Mode::TwoPlayers,
+        ..MatchSettings::default()
+    };
+    let mut spectators = Spectators::bind(0, settings).unwrap();
+    let mut court = Court::new(settings);
+    for _ in 0..300 {
+        court.update(STEP);
+    }
+    let joined = court.steps();
+
+    let mut spectate = Spectate::new(watch(&mut spectators), settings);
+    for _ in 0..3 * DELAY_STEPS {
+        court.update(STEP);
+        spectators.update().unwrap();
+        spectators.broadcast(&Message::State(court.snapshot()));
+        thread::sleep(Duration::from_millis(1));
+        spectate.tick(STEP);
+    }
+
+    let shown = spectate.court().steps();
+    assert!(
+        shown > joined,
+        "the spectator was shown the start of the match"
+    );
+    assert!(shown <= court.steps() - DELAY_STEPS);
+    assert!(shown >= court.steps() - 2 * DELAY_STEPS);
+}
+
+#[test]
+fn welcome_with_invalid_physics_is_rejected() {
+    let invalid = [
+        Physics {
+            ball_velocity_scalar: 0.5,
+            ..Physics::default()
+        },
+        Physics {
+            paddle_height: 0.975,
+            ..Physics::default()
+        },
+    ];
+    for physics in invalid {
+        let settings = MatchSettings {
+            physics,
+            ..MatchSettings::default()
+        };
+        let packet = encode(0, &Message::Welcome(settings, Netcode::Rollback));
+        let error = decode(&packet).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
+
+#[test]
+fn rollback_sessions_stay_in_sync_over_udp() {
+    let settings = MatchSettings {
+        mode: Mode::TwoPlayers,
+        rules: MatchRules::PRESETS[1].1,
+        seed: 5,
+        ..MatchSettings::default()
+    };
+    let (host, client) = connect(settings, Netcode::Rollback);
+    let mut peers = [
+        Session::new(settings, Player::One, UdpTransport::host(host, settings)),
+        Session::new(settings, Player::Two, UdpTransport::client(client)),
+    ];
+
+    // The players change their movement every half a second in opposite directions.
+    let start = Instant::now();
+    let mut step = 0u32;
+    while !peers.iter().all(|peer| peer.confirmed().is_over()) {
+        assert!(start.elapsed() < 60 * PATIENCE, "the match did not end");
+        let movement = if (step / 60).is_multiple_of(2) {
+            0.7
+        } else {
+            -0.7
+        };
+        peers[0].advance(movement).unwrap();
+        peers[1].advance(-movement).unwrap();
+        step += 1;
+    }
+
+    // The court stays as it is after the match, so the confirmed courts are the same on both ends
+    // even when one of the sessions has simulated more steps.
+    let [host, client] = &peers;
+    assert_eq!(
+        host.confirmed().snapshot().checksum(),
+        client.confirmed().snapshot().checksum()
+    );
+    assert_eq!(host.recording().inputs, client.recording().inputs);
+    assert!(!host.transport().has_left() && !client.transport().has_left());
+}
+
+#[test]
+fn rollback_host_welcomes_the_client_again_and_notices_a_bye() {
+    let settings = MatchSettings {
+        mode: Mode::TwoPlayers,
+        ..MatchSettings::default()
+    };
+    let (host, mut client) = connect(settings, Netcode::Rollback);
+    wait_for(&mut client, |message| match message {
+        Message::Welcome(..) => Some(()),
+        _ => None,
+    });
+    let mut host = Session::new(settings, Player::One, UdpTransport::host(host, settings));
+
+    // The client says hello again when the first welcome was lost.
+    client.send(&Message::Hello).unwrap();
+    let start = Instant::now();
+    let received = loop {
+        assert!(start.elapsed() < PATIENCE, "the host never answered");
+        host.poll().unwrap();
+        thread::sleep(Duration::from_millis(1));
+        let welcome = client
+            .receive()
+            .unwrap()
+            .into_iter()
+            .find_map(|message| match message {
+                Message::Welcome(settings, netcode) => Some((settings, netcode)),
+                _ => None,
+            });
+        if let Some(welcome) = welcome {
+            break welcome;
+        }
+    };
+    assert_eq!(received, (settings, Netcode::Rollback));
+
+    client.send(&Message::Bye).unwrap();
+    while !host.transport().has_left() {
+        assert!(start.elapsed() < 2 * PATIENCE, "the bye never arrived");
+        host.poll().unwrap();
+        thread::sleep(Duration::from_millis(1));
+    }
+}
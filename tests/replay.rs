@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use rust_pong::{
+    headless::{ScriptedInput, Simulation},
+    input::{Action, ActionEvent, Player},
+    recording::Recording,
+    rules::MatchRules,
+    scenes::{MatchSettings, Mode, Replay, Scene, Transition},
+    timer::STEP,
+};
+
+/// The maximum amount of simulated time for a single match.
+const TIME_LIMIT: Duration = Duration::from_secs(60 * 60);
+
+/// Build a script where both players use every kind of input at times which repeat until the limit.
+fn script() -> Vec<ScriptedInput> {
+    let mut script = Vec::new();
+    let mut time = Duration::from_millis(500);
+    while time < Duration::from_secs(120) {
+        let events = [
+            ActionEvent::Pressed(Action::MoveUp(Player::One)),
+            ActionEvent::Steered(Player::Two, 0.6),
+            ActionEvent::Released(Action::MoveUp(Player::One)),
+            ActionEvent::Pressed(Action::MoveDown(Player::One)),
+            ActionEvent::Aimed(Player::Two, 0.3),
+            ActionEvent::Released(Action::MoveDown(Player::One)),
+            ActionEvent::Steered(Player::Two, -0.9),
+        ];
+        for event in events {
+            script.push(ScriptedInput { time, event });
+            time += Duration::from_millis(170);
+        }
+    }
+    script
+}
+
+#[test]
+fn replay_ends_in_the_same_state_as_the_recorded_match() {
+    let settings = MatchSettings {
+        mode: Mode::TwoPlayers,
+        rules: MatchRules::PRESETS[1].1,
+        seed: 9,
+        ..MatchSettings::default()
+    };
+    let mut simulation = Simulation::new(settings, script());
+    assert!(simulation.run(TIME_LIMIT).finished);
+    let recorded = simulation.court();
+    assert!(!recorded.recording().inputs.is_empty());
+
+    // The recording is played back as it would be after saving it into a file.
+    let mut file = Vec::new();
+    recorded.recording().write(&mut file).unwrap();
+    let recording = Recording::read(&mut &file[..]).unwrap();
+    let mut replay = Replay::new(recording);
+    let mut time = Duration::ZERO;
+    while let Transition::Stay = replay.tick(STEP) {
+        time += STEP;
+        assert!(time < TIME_LIMIT, "the replay did not end");
+    }
+
+    let played = replay.court();
+    assert_eq!(played.steps(), recorded.steps());
+    assert_eq!(played.points(), recorded.points());
+    assert_eq!(played.sets(), recorded.sets());
+    assert_eq!(played.snapshot().checksum(), recorded.snapshot().checksum());
+}
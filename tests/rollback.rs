@@ -0,0 +1,76 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use rust_pong::{
+    headless::run_rollback,
+    input::Player,
+    rollback::{Loopback, Session},
+    rules::MatchRules,
+    scenes::{MatchSettings, Mode},
+    statistics::Statistics,
+};
+
+/// The maximum amount of simulated time for a single match.
+const TIME_LIMIT: Duration = Duration::from_secs(60 * 60);
+
+/// Build the settings of a short seeded match between two players.
+fn settings(seed: u64) -> MatchSettings {
+    MatchSettings {
+        mode: Mode::TwoPlayers,
+        rules: MatchRules::PRESETS[1].1,
+        seed,
+        ..MatchSettings::default()
+    }
+}
+
+#[test]
+fn peers_end_up_with_the_same_checksum_for_any_latency() {
+    let connections = [(0, 0), (16, 0), (50, 20), (80, 40), (150, 100)];
+    for (seed, (latency, jitter)) in connections.into_iter().enumerate() {
+        let latency = Duration::from_millis(latency);
+        let jitter = Duration::from_millis(jitter);
+        let rollback = run_rollback(settings(seed as u64), latency, jitter, TIME_LIMIT);
+        assert!(rollback.result.finished, "{:?} {:?}", latency, jitter);
+        assert_eq!(rollback.checksums.0, rollback.checksums.1);
+        assert!(rollback.converged, "{:?} {:?}", latency, jitter);
+    }
+}
+
+#[test]
+fn subscribers_are_notified_of_each_confirmed_goal_once() {
+    let (a, b) = Loopback::pair(Duration::from_millis(80), Duration::from_millis(40), 3);
+    let mut peers = [
+        Session::new(settings(3), Player::One, a),
+        Session::new(settings(3), Player::Two, b),
+    ];
+    let statistics = [(); 2].map(|_| Rc::new(RefCell::new(Statistics::default())));
+    for (peer, statistics) in peers.iter_mut().zip(&statistics) {
+        peer.subscribe(statistics.clone());
+    }
+
+    // The players change their movement every second, so the late inputs cause rollbacks.
+    let mut step = 0u32;
+    while !peers.iter().all(|peer| peer.confirmed().is_over()) {
+        let movement = if (step / 60).is_multiple_of(2) {
+            0.8
+        } else {
+            -0.6
+        };
+        for peer in &mut peers {
+            peer.advance(movement).unwrap();
+        }
+        step += 1;
+        assert!(step < 60 * 60 * 60, "the match did not end");
+    }
+
+    assert!(peers.iter().all(|peer| peer.rollbacks() > 0));
+    for (peer, statistics) in peers.iter().zip(&statistics) {
+        let expected = peer.confirmed().statistics();
+        let statistics = statistics.borrow();
+        assert_eq!(statistics.l_goals, expected.l_goals);
+        assert_eq!(statistics.r_goals, expected.r_goals);
+        assert_eq!(statistics.l_hits, expected.l_hits);
+        assert_eq!(statistics.r_hits, expected.r_hits);
+    }
+}